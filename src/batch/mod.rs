@@ -0,0 +1,130 @@
+use error::Error;
+use std::fs;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use util::STDIO_PATH;
+
+/// A problem file and where its solution goes
+#[derive(Clone, Debug)]
+pub struct BatchItem {
+	pub input: PathBuf,
+	pub output: PathBuf,
+}
+
+impl BatchItem {
+	/// Solution is written next to the input, with the extension swapped for `.o`.
+	/// Reading stdin, given as `-`, writes to stdout
+	pub fn new(input: PathBuf) -> BatchItem {
+		let output = if input == Path::new(STDIO_PATH) {
+			input.clone()
+		} else {
+			input.with_extension("o")
+		};
+		BatchItem { input, output }
+	}
+
+	pub fn writes_stdout(&self) -> bool {
+		self.output == Path::new(STDIO_PATH)
+	}
+}
+
+pub struct BatchResult {
+	pub item: BatchItem,
+	/// None if solving failed or the worker panicked
+	pub score: Option<u64>,
+	pub elapsed: Duration,
+}
+
+/// All `.in` files in the given directory, sorted by name
+pub fn input_files(dir: &Path) -> io::Result<Vec<BatchItem>> {
+	let mut inputs = Vec::new();
+	for entry in fs::read_dir(dir)? {
+		let path = entry?.path();
+		if path.is_file() && path.extension().is_some_and(|e| e == "in") {
+			inputs.push(path);
+		}
+	}
+
+	inputs.sort();
+	Ok(inputs.into_iter().map(BatchItem::new).collect())
+}
+
+/// Solves every item on a pool of `threads` workers.
+/// `solve` writes its messages to the given buffer, which is logged in one go once the item is done.
+/// Errors are logged along with it. Results are returned in the same order as the items
+pub fn run_batch<F>(items: Vec<BatchItem>, threads: usize, solve: F) -> Vec<BatchResult>
+where
+	F: Fn(&BatchItem, &mut String) -> Result<u64, Error> + Send + Sync + 'static,
+{
+	let num_items = items.len();
+	let queue = Arc::new(Mutex::new(items.into_iter().enumerate().collect::<Vec<_>>()));
+	let solve = Arc::new(solve);
+	let (sender, receiver) = mpsc::channel();
+
+	let workers: Vec<_> = (0..usize::max(threads, 1))
+		.map(|_| {
+			let queue = queue.clone();
+			let solve = solve.clone();
+			let sender = sender.clone();
+
+			thread::spawn(move || loop {
+				// pop from the front to keep the original order
+				let next = {
+					let mut queue = queue.lock().unwrap();
+					if queue.is_empty() {
+						None
+					} else {
+						Some(queue.remove(0))
+					}
+				};
+
+				let (idx, item) = match next {
+					Some(n) => n,
+					None => break,
+				};
+
+				let started = Instant::now();
+				let mut log = String::new();
+				let outcome = panic::catch_unwind(AssertUnwindSafe(|| solve(&item, &mut log)));
+				let elapsed = started.elapsed();
+
+				info!(
+					"\n ============= Input {} ==================\n\n{} ({:.2}s)",
+					item.input.display(),
+					log,
+					elapsed.as_secs_f64()
+				);
+				let score = match outcome {
+					Ok(Ok(score)) => Some(score),
+					Ok(Err(e)) => {
+						warn!("Solving {} failed: {}", item.input.display(), e);
+						None
+					}
+					Err(_) => {
+						warn!("Solving {} failed", item.input.display());
+						None
+					}
+				};
+
+				let _ = sender.send((idx, BatchResult { item, score, elapsed }));
+			})
+		})
+		.collect();
+
+	drop(sender);
+	let mut results: Vec<Option<BatchResult>> = (0..num_items).map(|_| None).collect();
+	for (idx, result) in receiver {
+		results[idx] = Some(result);
+	}
+
+	for w in workers {
+		w.join().expect("Batch worker crashed");
+	}
+
+	results.into_iter().map(|r| r.unwrap()).collect()
+}
@@ -0,0 +1,227 @@
+//! Readers for the vehicle routing benchmarks from the literature, projected onto the ride model.
+//!
+//! Both formats are mapped the same way where they overlap:
+//! - Coordinates are moved so that the depot sits at the origin, where every vehicle starts.
+//!   They can end up negative, the grid size is the span of all locations.
+//! - Distances are Manhattan rather than Euclidean, so route lengths and travel times aren't
+//!   comparable to the published results, only the relative quality of the heuristics is.
+//! - The horizon is the depot's due date and there is no bonus for starting on time.
+//! - Vehicle capacities are ignored, a vehicle carries one ride at a time anyway.
+//! - Service times are ignored, a ride takes exactly as long as its distance.
+//! - Fractional values are rounded to the nearest step.
+
+use error::Error;
+use scheduler::{Job, JobId, Problem};
+use std::error;
+use std::fmt;
+use util::{Coord, FileIOError, FileReader, TimeStep};
+
+#[derive(Debug)]
+pub enum BenchmarkError {
+	ReadError(Vec<FileIOError>),
+	/// Line number, starting at 1, and the line
+	MalformedLine(usize, String),
+	/// The header with the vehicle count, or the depot
+	MissingSection(&'static str),
+	/// A pickup and a delivery that don't point at each other
+	UnpairedTask(usize),
+	/// The converted rides don't make a valid problem
+	Invalid(Box<Error>),
+}
+
+impl fmt::Display for BenchmarkError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			BenchmarkError::ReadError(ref errs) => match errs.first() {
+				Some(e) => write!(f, "{} ({} error(s) in all)", e, errs.len()),
+				None => write!(f, "couldn't read the file"),
+			},
+			BenchmarkError::MalformedLine(line_no, ref line) => {
+				write!(f, "malformed line {}: {:?}", line_no, line)
+			}
+			BenchmarkError::MissingSection(section) => write!(f, "missing {}", section),
+			BenchmarkError::UnpairedTask(task) => write!(f, "task {} has no matching pickup or delivery", task),
+			BenchmarkError::Invalid(ref e) => write!(f, "invalid problem: {}", e),
+		}
+	}
+}
+
+impl error::Error for BenchmarkError {
+	fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+		match *self {
+			BenchmarkError::ReadError(ref errs) => errs.first().map(|e| e as &(dyn error::Error + 'static)),
+			BenchmarkError::Invalid(ref e) => Some(e.as_ref()),
+			_ => None,
+		}
+	}
+}
+
+/// A depot or customer, in the file's own units
+struct Location {
+	coord: Coord,
+	ready: TimeStep,
+	due: TimeStep,
+}
+
+fn numbers(line_no: usize, line: &str) -> Result<Vec<i64>, BenchmarkError> {
+	line.split_whitespace()
+		.map(|t| t.parse::<f64>().map(|v| v.round() as i64))
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(|_| BenchmarkError::MalformedLine(line_no + 1, line.to_string()))
+}
+
+fn location(fields: &[i64]) -> Location {
+	Location {
+		coord: Coord::new(fields[1] as i32, fields[2] as i32),
+		ready: fields[4] as TimeStep,
+		due: fields[5] as TimeStep,
+	}
+}
+
+/// Builds the problem out of (start, end, earliest start, latest finish) in file coordinates
+fn problem(
+	num_vehicles: i32,
+	depot: &Location,
+	rides: Vec<(Coord, Coord, TimeStep, TimeStep)>,
+) -> Result<Problem, BenchmarkError> {
+	let shift = |c: Coord| Coord::new(c.x - depot.coord.x, c.y - depot.coord.y);
+	let jobs: Vec<Job> = rides
+		.into_iter()
+		.enumerate()
+		.map(|(id, (start, end, earliest_start, latest_finish))| {
+			Job::new(id as JobId, shift(start), shift(end), earliest_start, latest_finish)
+		})
+		.collect();
+
+	let span = |f: &dyn Fn(&Coord) -> i32| {
+		let values = jobs.iter().flat_map(|j| vec![f(&j.start()), f(&j.end())]).chain(Some(0));
+		let (min, max) = values.fold((i32::MAX, i32::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)));
+		max - min + 1
+	};
+	let num_rows = span(&|c: &Coord| c.x);
+	let num_cols = span(&|c: &Coord| c.y);
+
+	Problem::from_jobs(num_rows, num_cols, num_vehicles, 0, depot.due, jobs)
+		.map_err(|e| BenchmarkError::Invalid(Box::new(e)))
+}
+
+/// Solomon VRPTW instance. Every customer becomes a ride from the depot to the customer, which
+/// has to arrive within the customer's time window. Every ride starts at the depot, so vehicles
+/// drive back there between rides, and that trip counts as travel time like any other
+pub fn solomon_from_lines(lines: &[String]) -> Result<Problem, BenchmarkError> {
+	let mut num_vehicles = None;
+	let mut locations: Vec<Location> = Vec::new();
+	let mut expect_vehicles = false;
+	let mut in_customers = false;
+
+	for (line_no, line) in lines.iter().enumerate() {
+		let trimmed = line.trim();
+		if trimmed.is_empty() {
+			continue;
+		}
+
+		if trimmed.starts_with("NUMBER") {
+			expect_vehicles = true;
+		} else if trimmed.starts_with("CUST") {
+			in_customers = true;
+		} else if expect_vehicles {
+			num_vehicles = Some(numbers(line_no, trimmed)?[0] as i32);
+			expect_vehicles = false;
+		} else if in_customers {
+			let fields = numbers(line_no, trimmed)?;
+			if fields.len() < 7 {
+				return Err(BenchmarkError::MalformedLine(line_no + 1, line.clone()));
+			}
+			locations.push(location(&fields));
+		}
+	}
+
+	let num_vehicles = num_vehicles.ok_or(BenchmarkError::MissingSection("VEHICLE"))?;
+	if locations.is_empty() {
+		return Err(BenchmarkError::MissingSection("CUSTOMER"));
+	}
+	let depot = locations.remove(0);
+
+	let rides = locations
+		.iter()
+		.map(|c| {
+			let dist = depot.coord.dist(&c.coord);
+			(depot.coord, c.coord, TimeStep::max(c.ready - dist, 0), c.due)
+		})
+		.collect();
+
+	problem(num_vehicles, &depot, rides)
+}
+
+/// Li & Lim PDPTW instance. Every pickup and delivery pair becomes a ride between the two.
+/// It may start once the pickup is ready, but not so early that it would arrive at the
+/// delivery before that is ready, and has to arrive by the delivery's due date
+pub fn li_lim_from_lines(lines: &[String]) -> Result<Problem, BenchmarkError> {
+	let mut rows = lines
+		.iter()
+		.enumerate()
+		.filter(|&(_, l)| !l.trim().is_empty());
+
+	let num_vehicles = match rows.next() {
+		Some((line_no, line)) => numbers(line_no, line)?[0] as i32,
+		None => return Err(BenchmarkError::MissingSection("vehicles")),
+	};
+
+	// index, x, y, demand, ready, due, service, pickup, delivery
+	let mut tasks: Vec<Vec<i64>> = Vec::new();
+	for (line_no, line) in rows {
+		let fields = numbers(line_no, line)?;
+		if fields.len() < 9 {
+			return Err(BenchmarkError::MalformedLine(line_no + 1, line.clone()));
+		}
+		tasks.push(fields);
+	}
+	if tasks.is_empty() {
+		return Err(BenchmarkError::MissingSection("depot"));
+	}
+
+	let depot = location(&tasks[0]);
+	let by_index = |index: i64| tasks.iter().position(|t| t[0] == index);
+
+	let mut rides = Vec::new();
+	for (idx, task) in tasks.iter().enumerate().skip(1) {
+		// pickups name their delivery, deliveries their pickup
+		if task[7] != 0 {
+			match by_index(task[7]) {
+				Some(p) if tasks[p][8] == task[0] => continue,
+				_ => return Err(BenchmarkError::UnpairedTask(idx)),
+			}
+		}
+
+		let delivery = match by_index(task[8]) {
+			Some(d) if tasks[d][7] == task[0] => &tasks[d],
+			_ => return Err(BenchmarkError::UnpairedTask(idx)),
+		};
+		let (pickup, delivery) = (location(task), location(delivery));
+		let dist = pickup.coord.dist(&delivery.coord);
+		rides.push((
+			pickup.coord,
+			delivery.coord,
+			TimeStep::max(pickup.ready, delivery.ready - dist),
+			delivery.due,
+		));
+	}
+
+	problem(num_vehicles, &depot, rides)
+}
+
+/// `-` reads stdin
+fn read_lines(path: &str) -> Result<Vec<String>, BenchmarkError> {
+	FileReader::open(path)
+		.map_err(|e| BenchmarkError::ReadError(vec![e]))?
+		.read_all_lines()
+		.map_err(BenchmarkError::ReadError)
+}
+
+pub fn read_solomon(path: &str) -> Result<Problem, BenchmarkError> {
+	solomon_from_lines(&read_lines(path)?)
+}
+
+pub fn read_li_lim(path: &str) -> Result<Problem, BenchmarkError> {
+	li_lim_from_lines(&read_lines(path)?)
+}
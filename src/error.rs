@@ -0,0 +1,111 @@
+//! The crate's error type, for everything that can go wrong between reading a problem and
+//! writing its solution. The more specific errors of each module convert into it with `?`.
+
+extern crate kdtree;
+
+use benchmark::BenchmarkError;
+use lp::LpError;
+use scheduler::JobId;
+use solution::SolutionError;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+use util::FileIOError;
+
+#[derive(Debug)]
+pub enum Error {
+	Io(FileIOError),
+	/// File name that isn't valid Unicode
+	NonUnicodePath(PathBuf),
+	/// Every line that couldn't be read
+	Read(Vec<FileIOError>),
+	/// Line number, starting at 1, and the line, for input lines that aren't six numbers
+	MalformedInput(usize, String),
+	/// Position and ID of a job that isn't numbered by its position
+	MisnumberedJob(usize, JobId),
+	/// The problem has no rides to simulate
+	NoJobs,
+	/// Vehicle ID and the job it was handed while it already had one lined up
+	JobAlreadyQueued(usize, JobId),
+	/// Vehicle ID and what it was asked to do in a state that doesn't allow it
+	InvalidVehicleState(usize, &'static str),
+	/// Dispatching got stuck in a state it can't get out of
+	DispatchFailed(&'static str),
+	/// A vehicle couldn't be added to the index of free vehicles
+	VehicleIndex(kdtree::ErrorKind),
+	Solution(SolutionError),
+	Benchmark(BenchmarkError),
+	Lp(LpError),
+}
+
+impl Display for Error {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match *self {
+			Error::Io(ref e) => write!(f, "I/O error: {}", e),
+			Error::NonUnicodePath(ref path) => write!(f, "Path isn't valid Unicode: {}", path.display()),
+			Error::Read(ref errs) => match errs.first() {
+				Some(e) => write!(f, "Couldn't read the input: {} ({} error(s) in all)", e, errs.len()),
+				None => write!(f, "Couldn't read the input"),
+			},
+			Error::MalformedInput(line_no, ref line) => {
+				write!(f, "Malformed input on line {}: {:?}", line_no, line)
+			}
+			Error::MisnumberedJob(idx, id) => write!(f, "Job {} has ID {}", idx, id),
+			Error::NoJobs => write!(f, "The problem has no rides"),
+			Error::JobAlreadyQueued(vehicle, job) => write!(
+				f,
+				"Vehicle {} was handed job {} with another one already lined up",
+				vehicle, job
+			),
+			Error::InvalidVehicleState(vehicle, what) => write!(f, "Vehicle {}: {}", vehicle, what),
+			Error::DispatchFailed(what) => write!(f, "Dispatch failed: {}", what),
+			Error::VehicleIndex(ref e) => write!(f, "Couldn't index vehicle: {:?}", e),
+			Error::Solution(ref e) => write!(f, "Invalid solution: {}", e),
+			Error::Benchmark(ref e) => write!(f, "Couldn't read benchmark instance: {}", e),
+			Error::Lp(ref e) => write!(f, "LP error: {}", e),
+		}
+	}
+}
+
+impl error::Error for Error {
+	fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+		match *self {
+			Error::Io(ref e) => Some(e),
+			Error::Read(ref errs) => errs.first().map(|e| e as &(dyn error::Error + 'static)),
+			Error::Solution(ref e) => Some(e),
+			Error::Benchmark(ref e) => Some(e),
+			Error::Lp(ref e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+impl From<FileIOError> for Error {
+	fn from(e: FileIOError) -> Error {
+		Error::Io(e)
+	}
+}
+
+impl From<SolutionError> for Error {
+	fn from(e: SolutionError) -> Error {
+		Error::Solution(e)
+	}
+}
+
+impl From<BenchmarkError> for Error {
+	fn from(e: BenchmarkError) -> Error {
+		Error::Benchmark(e)
+	}
+}
+
+impl From<LpError> for Error {
+	fn from(e: LpError) -> Error {
+		Error::Lp(e)
+	}
+}
+
+impl From<kdtree::ErrorKind> for Error {
+	fn from(e: kdtree::ErrorKind) -> Error {
+		Error::VehicleIndex(e)
+	}
+}
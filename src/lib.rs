@@ -1,3 +1,15 @@
+#[macro_use]
+pub mod logging;
+
+pub mod batch;
+pub mod benchmark;
+pub mod error;
+pub mod lp;
+pub mod portfolio;
+pub mod rng;
 pub mod scheduler;
+pub mod solution;
+pub mod solver;
 pub mod util;
+pub mod viz;
 mod vehicle;
@@ -0,0 +1,371 @@
+use scheduler::{JobId, Problem};
+use solution::{route_timings, Solution, SolutionError};
+use std::collections::{HashMap, HashSet};
+use std::error;
+use std::fmt;
+use util::{Coord, FileIOError, FileReader, FileWriter, TimeStep};
+
+/// Rides and vehicles handed to the MIP solver, everything else stays as it is
+#[derive(Clone, Debug, PartialEq)]
+pub struct Slice {
+	pub rides: Vec<JobId>,
+	pub vehicles: Vec<usize>,
+}
+
+impl Slice {
+	pub fn new(rides: Vec<JobId>, vehicles: Vec<usize>) -> Slice {
+		Slice { rides, vehicles }
+	}
+
+	pub fn all(problem: &Problem) -> Slice {
+		Slice {
+			rides: (0..problem.num_jobs()).collect(),
+			vehicles: (0..problem.num_vehicles() as usize).collect(),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum LpError {
+	ReadError(Vec<FileIOError>),
+	/// Line that looked like an arc but couldn't be made sense of
+	MalformedLine(String),
+	/// Ride the assignment uses that isn't part of the slice
+	UnknownRide(JobId),
+	TwoSuccessors(JobId),
+	TwoPredecessors(JobId),
+	/// Ride that is served but can't be reached from the depot, it is part of a cycle
+	Unreachable(JobId),
+	/// Routes found, vehicles in the slice
+	TooManyRoutes(usize, usize),
+	/// Ride the model served that the scorer says is late
+	Late(JobId),
+	Invalid(SolutionError),
+}
+
+impl fmt::Display for LpError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			LpError::ReadError(ref errs) => match errs.first() {
+				Some(e) => write!(f, "{} ({} error(s) in all)", e, errs.len()),
+				None => write!(f, "couldn't read the assignment"),
+			},
+			LpError::MalformedLine(ref line) => write!(f, "malformed arc: {:?}", line),
+			LpError::UnknownRide(id) => write!(f, "ride {} isn't part of the slice", id),
+			LpError::TwoSuccessors(id) => write!(f, "ride {} has two successors", id),
+			LpError::TwoPredecessors(id) => write!(f, "ride {} has two predecessors", id),
+			LpError::Unreachable(id) => write!(f, "ride {} is part of a cycle", id),
+			LpError::TooManyRoutes(routes, vehicles) => {
+				write!(f, "{} routes for {} vehicles", routes, vehicles)
+			}
+			LpError::Late(id) => write!(f, "ride {} is served late", id),
+			LpError::Invalid(ref e) => write!(f, "invalid solution: {}", e),
+		}
+	}
+}
+
+impl error::Error for LpError {
+	fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+		match *self {
+			LpError::ReadError(ref errs) => errs.first().map(|e| e as &(dyn error::Error + 'static)),
+			LpError::Invalid(ref e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+fn latest_start(problem: &Problem, ride: JobId) -> TimeStep {
+	let job = problem.job(ride);
+	TimeStep::min(job.latest_finish(), problem.max_tsteps()) - job.dist()
+}
+
+/// Appends a linear expression, broken over several lines to stay clear of the line length limit
+fn push_terms(out: &mut String, terms: &[String]) {
+	for (idx, term) in terms.iter().enumerate() {
+		if idx > 0 && idx % 8 == 0 {
+			*out += "\n   ";
+		}
+		if idx > 0 || term.starts_with('-') {
+			*out += if term.starts_with('-') { " - " } else { " + " };
+		}
+		*out += term.trim_start_matches('-');
+	}
+}
+
+/// Time-window vehicle routing model of the slice in CPLEX LP format.
+///
+/// `x_i_j` is set when ride `j` follows ride `i` on some vehicle, `x_o_j` when it's the first
+/// ride of a route. `y_i` is set for every ride served, `b_i` for every one started on time for
+/// the bonus, `s_i` is its start time. The fleet is the slice's vehicles, which all start at the
+/// origin at step 0. Rides that can't be served on time whatever happens are left out
+pub fn model_as_str(problem: &Problem, slice: &Slice) -> String {
+	let rides: Vec<JobId> = slice
+		.rides
+		.iter()
+		.cloned()
+		.filter(|&id| latest_start(problem, id) >= problem.job(id).earliest_start())
+		.collect();
+
+	// arcs whose rides can be served one after the other at all
+	let from_origin: Vec<JobId> = rides
+		.iter()
+		.cloned()
+		.filter(|&j| Coord::default().dist(&problem.job(j).start()) <= latest_start(problem, j))
+		.collect();
+	let mut arcs: Vec<(JobId, JobId)> = Vec::new();
+	for &i in rides.iter() {
+		let a = problem.job(i);
+		for &j in rides.iter() {
+			let b = problem.job(j);
+			if i != j && a.earliest_start() + a.dist() + a.end().dist(&b.start()) <= latest_start(problem, j) {
+				arcs.push((i, j));
+			}
+		}
+	}
+
+	let mut out = format!(
+		"\\ Ride scheduling, {} rides on {} vehicles\nMaximize\n obj: ",
+		rides.len(),
+		slice.vehicles.len()
+	);
+	let mut objective = Vec::new();
+	for &i in rides.iter() {
+		objective.push(format!("{} y_{}", problem.job(i).dist(), i));
+		if problem.ride_bonus() > 0 {
+			objective.push(format!("{} b_{}", problem.ride_bonus(), i));
+		}
+	}
+	push_terms(&mut out, &objective);
+
+	out += "\nSubject To\n";
+	let fleet: Vec<String> = from_origin.iter().map(|j| format!("x_o_{}", j)).collect();
+	if !fleet.is_empty() {
+		out += " fleet: ";
+		push_terms(&mut out, &fleet);
+		out += &format!(" <= {}\n", slice.vehicles.len());
+	}
+
+	let mut incoming: HashMap<JobId, Vec<String>> = HashMap::new();
+	let mut outgoing: HashMap<JobId, Vec<String>> = HashMap::new();
+	for &j in from_origin.iter() {
+		incoming.entry(j).or_default().push(format!("x_o_{}", j));
+	}
+	for &(i, j) in arcs.iter() {
+		incoming.entry(j).or_default().push(format!("x_{}_{}", i, j));
+		outgoing.entry(i).or_default().push(format!("x_{}_{}", i, j));
+	}
+
+	for &i in rides.iter() {
+		let job = problem.job(i);
+
+		// served exactly when something leads to it, and at most one ride follows
+		out += &format!(" in_{}: ", i);
+		let mut terms = incoming.remove(&i).unwrap_or_default();
+		terms.push(format!("-y_{}", i));
+		push_terms(&mut out, &terms);
+		out += " = 0\n";
+
+		if let Some(mut terms) = outgoing.remove(&i) {
+			out += &format!(" out_{}: ", i);
+			terms.push(format!("-y_{}", i));
+			push_terms(&mut out, &terms);
+			out += " <= 0\n";
+		}
+
+		if problem.ride_bonus() > 0 {
+			out += &format!(" bonus_{}: b_{} - y_{} <= 0\n", i, i, i);
+
+			// s_i <= earliest start + M (1 - b_i)
+			let m = latest_start(problem, i) - job.earliest_start();
+			if m > 0 {
+				out += &format!(" on_time_{}: s_{} + {} b_{} <= {}\n", i, i, m, i, job.earliest_start() + m);
+			}
+		}
+
+		let from_depot = Coord::default().dist(&job.start());
+		if from_depot > job.earliest_start() && from_origin.contains(&i) {
+			out += &format!(" reach_o_{}: s_{} - {} x_o_{} >= 0\n", i, i, from_depot, i);
+		}
+	}
+
+	// s_j >= s_i + len_i + dist_ij - M (1 - x_ij)
+	for &(i, j) in arcs.iter() {
+		let (a, b) = (problem.job(i), problem.job(j));
+		let gap = a.dist() + a.end().dist(&b.start());
+		let m = latest_start(problem, i) + gap - b.earliest_start();
+		if m > 0 {
+			out += &format!(
+				" reach_{}_{}: s_{} - s_{} - {} x_{}_{} >= {}\n",
+				i,
+				j,
+				j,
+				i,
+				m,
+				i,
+				j,
+				gap - m
+			);
+		}
+	}
+
+	out += "Bounds\n";
+	for &i in rides.iter() {
+		out += &format!(
+			" {} <= s_{} <= {}\n",
+			problem.job(i).earliest_start(),
+			i,
+			latest_start(problem, i)
+		);
+	}
+
+	out += "Binary\n";
+	let mut binaries: Vec<String> = fleet;
+	binaries.extend(arcs.iter().map(|&(i, j)| format!("x_{}_{}", i, j)));
+	binaries.extend(rides.iter().map(|i| format!("y_{}", i)));
+	if problem.ride_bonus() > 0 {
+		binaries.extend(rides.iter().map(|i| format!("b_{}", i)));
+	}
+	for chunk in binaries.chunks(8) {
+		out += &format!(" {}\n", chunk.join(" "));
+	}
+	out += "End\n";
+
+	out
+}
+
+pub fn write_lp(problem: &Problem, slice: &Slice, path: &str) -> Result<(), FileIOError> {
+	let mut writer = FileWriter::new(path)?;
+	writer.write_line(&model_as_str(problem, slice))?;
+	writer.finish()
+}
+
+/// Arc variable and its value from one line of a solution file. Understands CPLEX's XML
+/// `<variable name=".." value=".."/>` as well as the `name value` columns most other solvers write
+fn parse_arc(line: &str) -> Option<Result<(String, f64), LpError>> {
+	let attribute = |key: &str| -> Option<&str> {
+		let from = line.find(key)? + key.len();
+		let len = line[from..].find('"')?;
+		Some(&line[from..from + len])
+	};
+
+	let (name, value) = match attribute("name=\"") {
+		Some(name) => (name, attribute("value=\"")),
+		None => {
+			let line = line.split('#').next().unwrap();
+			let mut tokens = line.split_whitespace().skip_while(|t| !t.starts_with("x_"));
+			(tokens.next()?, tokens.next())
+		}
+	};
+	if !name.starts_with("x_") {
+		return None;
+	}
+
+	Some(match value.and_then(|v| v.parse::<f64>().ok()) {
+		Some(value) => Ok((name.to_string(), value)),
+		None => Err(LpError::MalformedLine(line.to_string())),
+	})
+}
+
+/// Turns a MIP solver's variable assignment for the model of the slice back into a solution.
+/// The slice's vehicles get the routes found, every other vehicle keeps its rides from `base`.
+/// The result is checked with the scorer, every ride the model serves has to be on time
+pub fn parse_assignment(
+	problem: &Problem,
+	slice: &Slice,
+	base: &Solution,
+	lines: &[String],
+) -> Result<Solution, LpError> {
+	let in_slice: HashSet<JobId> = slice.rides.iter().cloned().collect();
+	let ride = |s: &str, line: &str| -> Result<JobId, LpError> {
+		let id = s
+			.parse::<JobId>()
+			.map_err(|_| LpError::MalformedLine(line.to_string()))?;
+		if in_slice.contains(&id) {
+			Ok(id)
+		} else {
+			Err(LpError::UnknownRide(id))
+		}
+	};
+
+	let mut firsts: Vec<JobId> = Vec::new();
+	let mut next: HashMap<JobId, JobId> = HashMap::new();
+	let mut has_predecessor: HashSet<JobId> = HashSet::new();
+	for line in lines {
+		let (name, value) = match parse_arc(line) {
+			Some(arc) => arc?,
+			None => continue,
+		};
+		if value < 0.5 {
+			continue;
+		}
+
+		let parts: Vec<&str> = name.split('_').collect();
+		if parts.len() != 3 {
+			return Err(LpError::MalformedLine(line.clone()));
+		}
+		let to = ride(parts[2], line)?;
+		if !has_predecessor.insert(to) {
+			return Err(LpError::TwoPredecessors(to));
+		}
+
+		if parts[1] == "o" {
+			firsts.push(to);
+		} else {
+			let from = ride(parts[1], line)?;
+			if next.insert(from, to).is_some() {
+				return Err(LpError::TwoSuccessors(from));
+			}
+		}
+	}
+
+	if firsts.len() > slice.vehicles.len() {
+		return Err(LpError::TooManyRoutes(firsts.len(), slice.vehicles.len()));
+	}
+	firsts.sort();
+
+	let mut rides = base.rides().to_vec();
+	rides.resize(problem.num_vehicles() as usize, Vec::new());
+	for &v in slice.vehicles.iter() {
+		rides[v].clear();
+	}
+
+	let mut reached = HashSet::new();
+	for (&v, &first) in slice.vehicles.iter().zip(firsts.iter()) {
+		let mut cur = Some(first);
+		while let Some(id) = cur {
+			reached.insert(id);
+			rides[v].push(id);
+			cur = next.get(&id).cloned();
+		}
+	}
+	if let Some(&id) = has_predecessor.iter().find(|id| !reached.contains(id)) {
+		return Err(LpError::Unreachable(id));
+	}
+
+	let solution = Solution::new(rides);
+	solution.validate(problem).map_err(LpError::Invalid)?;
+	for &v in slice.vehicles.iter() {
+		if let Some(t) = route_timings(problem, &solution.rides()[v])
+			.iter()
+			.find(|t| !t.on_time())
+		{
+			return Err(LpError::Late(t.id));
+		}
+	}
+
+	Ok(solution)
+}
+
+pub fn read_assignment(
+	problem: &Problem,
+	slice: &Slice,
+	base: &Solution,
+	path: &str,
+) -> Result<Solution, LpError> {
+	let lines = FileReader::new(path)
+		.map_err(|e| LpError::ReadError(vec![e]))?
+		.read_all_lines()
+		.map_err(LpError::ReadError)?;
+
+	parse_assignment(problem, slice, base, &lines)
+}
@@ -1,43 +1,401 @@
+#[macro_use]
 extern crate google_hashcode18_prelim as root;
-extern crate itertools;
 
-use root::scheduler::JobScheduler;
+use root::batch::{input_files, run_batch, BatchItem};
+use root::benchmark::{read_li_lim, read_solomon};
+use root::error::Error;
+use root::logging::{self, Level};
+use root::portfolio::{run_portfolio, PortfolioEntry};
+use root::rng::Rng;
+use root::scheduler::{JobScheduler, Problem};
+use root::solution::WriteOutcome;
+use root::solver::genetic::genetic;
+use root::solver::lns::lns;
+use root::solver::tabu::tabu;
+use root::solver::{insert_unassigned, GaParams, LnsParams, TabuParams};
 use root::util::{FileReader, FileWriter};
-use self::itertools::Itertools;
+use root::viz::dashboard::Dashboard;
+use root::viz::dot::{write_dot, GraphFilter};
+use root::viz::frames::write_frames;
+use root::viz::gantt::write_gantt;
+use root::viz::heatmap::{write_ppm, Density, Heatmap};
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Copy, Clone)]
+enum Format {
+	HashCode,
+	Solomon,
+	LiLim,
+}
+
+fn usage() -> ! {
+	println!(
+		"Usage: google_hashcode18_prelim [--portfolio] [--keep-better] [--genetic GENERATIONS] [--lns ITERATIONS] [--tabu ITERATIONS] [--format FORMAT] [--dot FROM:TO] [--gantt] [--heatmap BIN] [--frames STEPS] [--dashboard] [--log-level LEVEL] [--threads N] [--seed N] [--random-ties] [DIR | FILE.in ...]"
+	);
+	println!("Solves every .in file in DIR (./data by default), writing the solutions next to them as .o files");
+	println!("--random-ties breaks ties between equally near vehicles with the seeded generator when not running the portfolio");
+	println!("The seed is printed with the results, pass it back with --seed (and --random-ties if it says so) to reproduce a run");
+	println!("Rides left over by the schedule are inserted wherever they fit without costing points");
+	println!("Passing - as the input reads it from stdin and writes the solution to stdout");
+	println!("--keep-better leaves existing .o files alone unless the new solution scores more");
+	println!("--genetic evolves a population grown from the schedule, keeping the best individual");
+	println!("--format reads the inputs as hashcode (the default), solomon or li-lim files, pass the files themselves for the latter two");
+	println!("--dot writes the graph of rides that can follow each other, within the given time window, as FILE.dot");
+	println!("--gantt replays the solution through the simulation and charts every vehicle's tasks as FILE.gantt.svg");
+	println!("--heatmap writes ride start, end and missed ride densities over BIN x BIN blocks of the grid as FILE.starts.ppm, FILE.ends.ppm and FILE.missed.ppm");
+	println!("--dashboard shows the simulation's progress and a map of the fleet while it runs, one input at a time");
+	println!("--log-level is one of error, warn, info (the default), debug for every dispatch decision or trace for every vehicle task");
+	println!("--frames draws the vehicles and the rides still waiting every STEPS steps, as SVG files in FILE.frames/");
+	println!("--lns improves the schedule with a large neighbourhood search afterwards");
+	println!("--tabu does the same with a tabu search over ride relocations and swaps");
+	process::exit(1);
+}
 
 fn main() {
-	let input = vec![
-		".\\data\\a_example.in",
-		".\\data\\b_should_be_easy.in",
-		".\\data\\c_no_hurry.in",
-		".\\data\\d_metropolis.in",
-		".\\data\\e_high_bonus.in",
-	];
-	let output = vec![
-		".\\data\\a_example.o",
-		".\\data\\b_should_be_easy.o",
-		".\\data\\c_no_hurry.o",
-		".\\data\\d_metropolis.o",
-		".\\data\\e_high_bonus.o",
-	];
+	let mut portfolio = false;
+	let mut format = Format::HashCode;
+	let mut threads = thread::available_parallelism().map_or(1, |n| n.get());
+	let mut seed = None;
+	let mut generations = 0;
+	let mut lns_iterations = 0;
+	let mut tabu_iterations = 0;
+	let mut dot_window = None;
+	let mut gantt = false;
+	let mut heatmap_bin = None;
+	let mut frame_interval = None;
+	let mut dashboard = false;
+	let mut keep_better = false;
+	let mut random_ties = false;
+	let mut paths = Vec::new();
+
+	let mut args = env::args_os().skip(1);
+	while let Some(arg) = args.next() {
+		// file names don't have to be valid Unicode, options do
+		let arg = match arg.into_string() {
+			Ok(a) => a,
+			Err(path) => {
+				paths.push(PathBuf::from(path));
+				continue;
+			}
+		};
+
+		match arg.as_str() {
+			"--portfolio" => portfolio = true,
+			"--gantt" => gantt = true,
+			"--dashboard" => dashboard = true,
+			"--keep-better" => keep_better = true,
+			"--random-ties" => random_ties = true,
+			"--threads" => {
+				threads = match next_str(&mut args).and_then(|n| n.parse::<usize>().ok()) {
+					Some(n) if n > 0 => n,
+					_ => usage(),
+				}
+			}
+			"--genetic" => {
+				generations = match next_str(&mut args).and_then(|n| n.parse::<usize>().ok()) {
+					Some(n) => n,
+					None => usage(),
+				}
+			}
+			"--lns" => {
+				lns_iterations = match next_str(&mut args).and_then(|n| n.parse::<usize>().ok()) {
+					Some(n) => n,
+					None => usage(),
+				}
+			}
+			"--tabu" => {
+				tabu_iterations = match next_str(&mut args).and_then(|n| n.parse::<usize>().ok()) {
+					Some(n) => n,
+					None => usage(),
+				}
+			}
+			"--heatmap" => {
+				heatmap_bin = match next_str(&mut args).and_then(|n| n.parse::<i32>().ok()) {
+					Some(n) if n > 0 => Some(n),
+					_ => usage(),
+				}
+			}
+			"--frames" => {
+				frame_interval = match next_str(&mut args).and_then(|n| n.parse::<i32>().ok()) {
+					Some(n) if n > 0 => Some(n),
+					_ => usage(),
+				}
+			}
+			"--dot" => {
+				let window = next_str(&mut args).and_then(|w| {
+					let (from, to) = w.split_once(':')?;
+					Some((from.parse::<i32>().ok()?, to.parse::<i32>().ok()?))
+				});
+				dot_window = match window {
+					Some(w) => Some(w),
+					None => usage(),
+				}
+			}
+			"--log-level" => {
+				match next_str(&mut args).and_then(|l| l.parse::<Level>().ok()) {
+					Some(level) => logging::set_max_level(level),
+					None => usage(),
+				}
+			}
+			"--format" => {
+				format = match next_str(&mut args).as_deref() {
+					Some("hashcode") => Format::HashCode,
+					Some("solomon") => Format::Solomon,
+					Some("li-lim") => Format::LiLim,
+					_ => usage(),
+				}
+			}
+			"--seed" => {
+				seed = match next_str(&mut args).and_then(|n| n.parse::<u64>().ok()) {
+					Some(n) => Some(n),
+					None => usage(),
+				}
+			}
+			"-h" | "--help" => usage(),
+			a if a.starts_with("--") => usage(),
+			a => paths.push(PathBuf::from(a)),
+		}
+	}
+
+	// dashboards of inputs solved side by side would draw over each other
+	if dashboard {
+		threads = 1;
+	}
+
+	if paths.is_empty() {
+		paths.push(Path::new(".").join("data"));
+	}
+
+	let mut items = Vec::new();
+	for p in paths {
+		if p.is_dir() {
+			match input_files(&p) {
+				Ok(inputs) => items.extend(inputs),
+				Err(e) => {
+					error!("Couldn't read input directory {}: {}", p.display(), e);
+					process::exit(1);
+				}
+			}
+		} else {
+			items.push(BatchItem::new(p));
+		}
+	}
+
+	// the extra files are named after the input, which stdin doesn't have
+	let writes_files = dot_window.is_some() || gantt || heatmap_bin.is_some() || frame_interval.is_some();
+	if writes_files && items.iter().any(|i| i.writes_stdout()) {
+		error!("--dot, --gantt, --heatmap and --frames need an input file to name their output after, not -");
+		process::exit(1);
+	}
+
+	let seed = seed.unwrap_or_else(|| Rng::from_time().seed());
+	// everything it takes to reproduce the run
+	let seed_note = if random_ties {
+		format!("Seed: {} | Random ties", seed)
+	} else {
+		format!("Seed: {}", seed)
+	};
+	info!("{}", seed_note);
+
+	let results = run_batch(items, threads, move |item, log| {
+		let input = path_str(&item.input)?;
+		let problem = match format {
+			Format::HashCode => Problem::new(FileReader::open(input)?)?,
+			Format::Solomon => read_solomon(input)?,
+			Format::LiLim => read_li_lim(input)?,
+		};
+		let (solution, score) = if portfolio {
+			let report = run_portfolio(&problem, &PortfolioEntry::defaults(), seed);
+			let score = report.best_score();
+
+			*log += &format!("{}", report);
+			(report.into_best(), score)
+		} else {
+			let mut runner = JobScheduler::from_problem(&problem);
+			if random_ties {
+				runner.set_rng(Rng::new(seed));
+			}
+			if dashboard {
+				runner.set_dashboard(Dashboard::new(Duration::from_millis(250)).with_map(&problem, 60));
+			}
+			runner.clear_observers();
+			runner.run()?;
+
+			*log += &format!("{}\n", runner.summary());
+			(Some(runner.solution()), runner.calculate_score())
+		};
+
+		let (solution, score) = match solution {
+			Some(solution) => {
+				let (recovered, report) = insert_unassigned(&problem, &solution)?;
+				*log += &format!("{}\n", report);
+				(Some(recovered), report.score_after)
+			}
+			None => (None, score),
+		};
+
+		let (solution, score) = match solution {
+			Some(initial) if generations > 0 => {
+				let params = GaParams {
+					generations,
+					..GaParams::default()
+				};
+				let evolved = genetic(&problem, &initial, &params, &mut Rng::new(seed))?;
+				let evolved_score = evolved.score(&problem)?;
+
+				*log += &format!(
+					"Genetic | Generations: {} | Score: {} -> {}\n",
+					generations,
+					initial.score(&problem)?,
+					evolved_score
+				);
+				(Some(evolved), evolved_score)
+			}
+			s => (s, score),
+		};
+
+		let (solution, score) = match solution {
+			Some(initial) if lns_iterations > 0 => {
+				let params = LnsParams {
+					iterations: lns_iterations,
+					..LnsParams::default()
+				};
+				let improved = lns(&problem, &initial, &params, &mut Rng::new(seed))?;
+				let improved_score = improved.score(&problem)?;
+
+				*log += &format!(
+					"LNS | Iterations: {} | Score: {} -> {}\n",
+					lns_iterations,
+					initial.score(&problem)?,
+					improved_score
+				);
+				(Some(improved), improved_score)
+			}
+			s => (s, score),
+		};
+
+		let (solution, score) = match solution {
+			Some(initial) if tabu_iterations > 0 => {
+				let params = TabuParams {
+					iterations: tabu_iterations,
+					..TabuParams::default()
+				};
+				let improved = tabu(&problem, &initial, &params, &mut Rng::new(seed))?;
+				let improved_score = improved.score(&problem)?;
+
+				*log += &format!(
+					"Tabu | Iterations: {} | Score: {} -> {}\n",
+					tabu_iterations,
+					initial.score(&problem)?,
+					improved_score
+				);
+				(Some(improved), improved_score)
+			}
+			s => (s, score),
+		};
+
+		if let Some(solution) = solution {
+			let output = path_str(&item.output)?;
+			if item.writes_stdout() {
+				let mut out = FileWriter::stdout();
+				solution.write_to(&mut out)?;
+				out.finish()?;
+			} else if keep_better {
+				if let WriteOutcome::Kept(existing) = solution.write_unless_worse(&problem, output)? {
+					*log += &format!("Kept {}, it scores {}\n", output, existing);
+				}
+			} else {
+				solution.write(output)?;
+			}
+
+			if let Some(window) = dot_window {
+				let filter = GraphFilter {
+					window: Some(window),
+					..GraphFilter::default()
+				};
+				let path = item.input.with_extension("dot");
+				write_dot(&problem, &filter, Some(&solution), path_str(&path)?)?;
+				*log += &format!("Graph written to {}\n", path.display());
+			}
+
+			if gantt {
+				let mut replay = JobScheduler::from_problem(&problem);
+				replay.follow_solution(&solution);
+				replay.clear_observers();
+				replay.run()?;
+
+				let path = item.input.with_extension("gantt.svg");
+				write_gantt(&problem, &replay.segments(), path_str(&path)?)?;
+				*log += &format!("Chart written to {}\n", path.display());
+			}
+
+			if let Some(bin) = heatmap_bin {
+				let maps = [
+					(Density::Starts, "starts.ppm"),
+					(Density::Ends, "ends.ppm"),
+					(Density::Missed, "missed.ppm"),
+				];
+				for &(kind, extension) in maps.iter() {
+					let heatmap = Heatmap::density(&problem, kind, Some(&solution), bin);
+					let path = item.input.with_extension(extension);
+					write_ppm(&heatmap, path_str(&path)?)?;
+				}
+				*log += &format!("Heatmaps written to {}.{{starts,ends,missed}}.ppm\n", item.input.with_extension("").display());
+			}
+
+			if let Some(every) = frame_interval {
+				let dir = item.input.with_extension("frames");
+				let count = write_frames(&problem, &solution, every, path_str(&dir)?)?;
+				*log += &format!("{} frames written to {}\n", count, dir.display());
+			}
+		}
+
+		Ok(score)
+	});
+
+	// keep the table out of the solution when that goes to stdout
+	let to_stdout = results.iter().any(|r| r.item.writes_stdout());
+	let print = |line: String| {
+		if to_stdout {
+			eprintln!("{}", line);
+		} else {
+			println!("{}", line);
+		}
+	};
+
 	let mut total_score: u64 = 0;
+	print(String::new());
+	for r in results.iter() {
+		let score = match r.score {
+			Some(s) => s.to_string(),
+			None => "failed".to_string(),
+		};
+		print(format!(
+			"{:<40} {:>12} {:>9.2}s",
+			r.item.input.display(),
+			score,
+			r.elapsed.as_secs_f64()
+		));
+		total_score += r.score.unwrap_or(0);
+	}
 
-	input
-		.iter()
-		.zip(output.iter())
-		.collect_vec()
-		.into_iter()
-		.foreach(|(i, o)| {
-			let input = FileReader::new(i).expect("Couldn't open input file");
-			let mut output = FileWriter::new(o).expect("Couldn't open output file");
-			let mut runner = JobScheduler::new(input);
+	print(format!("\n\nTotal score: {} | {}", total_score, seed_note));
 
-			println!("\n\n ============= Input {} ==================\n\n", i);
+	if results.iter().any(|r| r.score.is_none()) {
+		process::exit(1);
+	}
+}
 
-			runner.run();
-			runner.write_output(&mut output);
-			total_score += runner.calculate_score();
-		});
+fn next_str<I: Iterator<Item = OsString>>(args: &mut I) -> Option<String> {
+	args.next().and_then(|a| a.into_string().ok())
+}
 
-	println!("\n\nTotal score: {}", total_score);
+/// The file functions take paths as strings, which not every file name fits in
+fn path_str(path: &Path) -> Result<&str, Error> {
+	path.to_str().ok_or_else(|| Error::NonUnicodePath(path.to_path_buf()))
 }
@@ -0,0 +1,246 @@
+use error::Error;
+use scheduler::{DispatchStrategy, JobScheduler, Problem, RepositionPolicy};
+use rng::Rng;
+use solution::{Solution, SolutionError};
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A single scheduler configuration to try
+#[derive(Clone, Debug)]
+pub struct PortfolioEntry {
+	name: String,
+	strategy: DispatchStrategy,
+	reposition: RepositionPolicy,
+	random_ties: bool,
+	book_ahead: bool,
+}
+
+impl PortfolioEntry {
+	pub fn new(name: &str, strategy: DispatchStrategy, reposition: RepositionPolicy) -> PortfolioEntry {
+		PortfolioEntry {
+			name: name.to_string(),
+			strategy,
+			reposition,
+			random_ties: false,
+			book_ahead: false,
+		}
+	}
+
+	/// Break ties between equally near vehicles at random
+	pub fn with_random_ties(mut self) -> PortfolioEntry {
+		self.random_ties = true;
+		self
+	}
+
+	/// Line up vehicles' next jobs while they're still driving the current one
+	pub fn with_book_ahead(mut self) -> PortfolioEntry {
+		self.book_ahead = true;
+		self
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Configurations that did well on at least one of the contest inputs
+	pub fn defaults() -> Vec<PortfolioEntry> {
+		let bonus_aware = |bonus_weight| DispatchStrategy::BonusAware {
+			bonus_weight,
+			horizon: 20000,
+		};
+
+		vec![
+			PortfolioEntry::new("funky", DispatchStrategy::Funky, RepositionPolicy::Stay),
+			PortfolioEntry::new("funky+random ties", DispatchStrategy::Funky, RepositionPolicy::Stay)
+				.with_random_ties(),
+			PortfolioEntry::new(
+				"funky+reposition",
+				DispatchStrategy::Funky,
+				RepositionPolicy::TowardDemand(100),
+			),
+			PortfolioEntry::new("funky+book ahead", DispatchStrategy::Funky, RepositionPolicy::Stay)
+				.with_book_ahead(),
+			PortfolioEntry::new("bonus-aware x0.25", bonus_aware(0.25), RepositionPolicy::Stay),
+			PortfolioEntry::new("bonus-aware x1", bonus_aware(1.0), RepositionPolicy::Stay),
+			PortfolioEntry::new("bonus-aware x2", bonus_aware(2.0), RepositionPolicy::Stay),
+			PortfolioEntry::new("bonus-aware x1+book ahead", bonus_aware(1.0), RepositionPolicy::Stay)
+				.with_book_ahead(),
+		]
+	}
+}
+
+pub enum EntryOutcome {
+	Scored(u64),
+	Invalid(SolutionError),
+	/// The simulation stopped with an error
+	Failed(Error),
+	/// The worker thread panicked
+	Crashed,
+}
+
+pub struct EntryResult {
+	pub name: String,
+	pub outcome: EntryOutcome,
+	pub elapsed: Duration,
+	/// Only set for entries that made random choices
+	pub seed: Option<u64>,
+}
+
+pub struct PortfolioReport {
+	seed: u64,
+	results: Vec<EntryResult>,
+	best: Option<(usize, Solution)>,
+	/// 0 without a best solution
+	best_score: u64,
+}
+
+impl PortfolioReport {
+	/// Passing this to `run_portfolio` with the same entries reproduces the report
+	pub fn seed(&self) -> u64 {
+		self.seed
+	}
+
+	pub fn results(&self) -> &[EntryResult] {
+		&self.results
+	}
+
+	/// Highest-scoring valid solution and the index of the entry that produced it
+	pub fn best(&self) -> Option<(usize, &Solution)> {
+		self.best.as_ref().map(|&(idx, ref s)| (idx, s))
+	}
+
+	pub fn best_score(&self) -> u64 {
+		self.best_score
+	}
+
+	pub fn into_best(self) -> Option<Solution> {
+		self.best.map(|(_, s)| s)
+	}
+}
+
+impl Display for PortfolioReport {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		let width = self.results
+			.iter()
+			.map(|r| r.name.len())
+			.max()
+			.unwrap_or(0)
+			.max("Strategy".len());
+
+		writeln!(f, "  Seed: {}", self.seed)?;
+		writeln!(
+			f,
+			"  {:<w$} | {:>12} | {:>10} | {:>20}",
+			"Strategy",
+			"Score",
+			"Time",
+			"Seed",
+			w = width
+		)?;
+		writeln!(
+			f,
+			"  {}-+-{}-+-{}-+-{}",
+			"-".repeat(width),
+			"-".repeat(12),
+			"-".repeat(10),
+			"-".repeat(20)
+		)?;
+
+		for (idx, r) in self.results.iter().enumerate() {
+			let score = match r.outcome {
+				EntryOutcome::Scored(s) => s.to_string(),
+				EntryOutcome::Invalid(ref e) => e.to_string(),
+				EntryOutcome::Failed(ref e) => e.to_string(),
+				EntryOutcome::Crashed => "crashed".to_string(),
+			};
+			let marker = match self.best {
+				Some((best, _)) if best == idx => "*",
+				_ => " ",
+			};
+			let seed = match r.seed {
+				Some(s) => s.to_string(),
+				None => "-".to_string(),
+			};
+
+			writeln!(
+				f,
+				"{} {:<w$} | {:>12} | {:>9.2}s | {:>20}",
+				marker,
+				r.name,
+				score,
+				r.elapsed.as_secs_f64(),
+				seed,
+				w = width
+			)?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Runs every entry on its own thread and keeps the best valid solution.
+/// Each entry draws from its own generator, forked from `seed` in entry order
+pub fn run_portfolio(problem: &Problem, entries: &[PortfolioEntry], seed: u64) -> PortfolioReport {
+	let problem = Arc::new(problem.clone());
+	let mut rng = Rng::new(seed);
+
+	let handles: Vec<_> = entries
+		.iter()
+		.cloned()
+		.map(|entry| {
+			let problem = problem.clone();
+			let rng = rng.fork();
+			thread::spawn(move || {
+				let started = Instant::now();
+				let mut runner = JobScheduler::from_problem(&problem);
+				runner.set_dispatch_strategy(entry.strategy);
+				runner.set_reposition_policy(entry.reposition);
+				runner.set_book_ahead(entry.book_ahead);
+				if entry.random_ties {
+					runner.set_rng(rng);
+				}
+				runner.clear_observers();
+				let solution = runner.run().map(|_| runner.solution());
+
+				(solution, started.elapsed(), runner.seed())
+			})
+		})
+		.collect();
+
+	let mut results = Vec::with_capacity(entries.len());
+	let mut best: Option<(usize, Solution)> = None;
+	let mut best_score = 0;
+
+	for (idx, (entry, handle)) in entries.iter().zip(handles).enumerate() {
+		let (outcome, elapsed, seed) = match handle.join() {
+			Ok((Err(e), elapsed, seed)) => (EntryOutcome::Failed(e), elapsed, seed),
+			Ok((Ok(solution), elapsed, seed)) => match solution.score(&problem) {
+				Ok(score) => {
+					if best.is_none() || score > best_score {
+						best_score = score;
+						best = Some((idx, solution));
+					}
+					(EntryOutcome::Scored(score), elapsed, seed)
+				}
+				Err(e) => (EntryOutcome::Invalid(e), elapsed, seed),
+			},
+			Err(_) => (EntryOutcome::Crashed, Duration::default(), None),
+		};
+
+		results.push(EntryResult {
+			name: entry.name().to_string(),
+			outcome,
+			elapsed,
+			seed,
+		});
+	}
+
+	PortfolioReport {
+		seed,
+		results,
+		best,
+		best_score,
+	}
+}
@@ -0,0 +1,111 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn splitmix64(state: &mut u64) -> u64 {
+	*state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+	let mut z = *state;
+	z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+	z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+	z ^ (z >> 31)
+}
+
+/// Seeded xoshiro256** generator. The same seed always produces the same sequence,
+/// so every stochastic component should draw from one of these and report `seed()`
+#[derive(Clone, Debug)]
+pub struct Rng {
+	seed: u64,
+	state: [u64; 4],
+}
+
+impl Rng {
+	pub fn new(seed: u64) -> Rng {
+		let mut sm = seed;
+		let state = [
+			splitmix64(&mut sm),
+			splitmix64(&mut sm),
+			splitmix64(&mut sm),
+			splitmix64(&mut sm),
+		];
+
+		Rng { seed, state }
+	}
+
+	/// Seeded from the system clock, use `seed()` to reproduce the run later
+	pub fn from_time() -> Rng {
+		let now = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.unwrap_or_default();
+		let mut sm = now.as_secs() ^ u64::from(now.subsec_nanos()).rotate_left(32);
+		Rng::new(splitmix64(&mut sm))
+	}
+
+	pub fn seed(&self) -> u64 {
+		self.seed
+	}
+
+	pub fn next_u64(&mut self) -> u64 {
+		let out = self.state[1]
+			.wrapping_mul(5)
+			.rotate_left(7)
+			.wrapping_mul(9);
+		let t = self.state[1] << 17;
+
+		self.state[2] ^= self.state[0];
+		self.state[3] ^= self.state[1];
+		self.state[1] ^= self.state[2];
+		self.state[0] ^= self.state[3];
+		self.state[2] ^= t;
+		self.state[3] = self.state[3].rotate_left(45);
+
+		out
+	}
+
+	/// Uniform in [0, 1)
+	pub fn next_f64(&mut self) -> f64 {
+		(self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+	}
+
+	/// Uniform in [0, n), n must be positive
+	pub fn below(&mut self, n: usize) -> usize {
+		assert!(n > 0);
+
+		// reject the biased tail
+		let n = n as u64;
+		let zone = u64::MAX - (u64::MAX % n);
+		loop {
+			let r = self.next_u64();
+			if r < zone {
+				return (r % n) as usize;
+			}
+		}
+	}
+
+	/// Uniform in [lo, hi)
+	pub fn range(&mut self, lo: i32, hi: i32) -> i32 {
+		assert!(lo < hi);
+		lo + self.below((hi - lo) as usize) as i32
+	}
+
+	pub fn chance(&mut self, p: f64) -> bool {
+		self.next_f64() < p
+	}
+
+	pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+		if items.is_empty() {
+			None
+		} else {
+			Some(&items[self.below(items.len())])
+		}
+	}
+
+	pub fn shuffle<T>(&mut self, items: &mut [T]) {
+		for i in (1..items.len()).rev() {
+			let j = self.below(i + 1);
+			items.swap(i, j);
+		}
+	}
+
+	/// Independent generator for a sub-task, its seed is drawn from this one
+	pub fn fork(&mut self) -> Rng {
+		Rng::new(self.next_u64())
+	}
+}
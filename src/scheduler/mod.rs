@@ -5,16 +5,27 @@ use self::itertools::Itertools;
 use self::kdtree::KdTree;
 use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::HashMap;
-use std::fmt::{Debug, Error, Formatter};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{self, Debug, Formatter};
 use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::mem;
 use std::rc::Rc;
 use std::vec::Vec;
-use util::{cmp_i32, Coord, FileReader, FileWriter, TimeStep};
+use error::Error;
+use rng::Rng;
+use solution::Solution;
+use util::{cmp_i32, Coord, FileReader, TimeStep};
 use vehicle::Vehicle;
+use viz::dashboard::{Dashboard, Status};
+
+pub mod observer;
+
+pub use self::observer::{Observer, SummaryPrinter};
 
 pub type JobId = i32;
 
+#[derive(Clone)]
 pub struct Job {
 	id: JobId,
 	start: Coord,
@@ -24,14 +35,23 @@ pub struct Job {
 }
 
 impl Job {
+	pub fn new(id: JobId, start: Coord, end: Coord, earliest_start: TimeStep, latest_finish: TimeStep) -> Job {
+		Job {
+			id,
+			start,
+			end,
+			earliest_start,
+			latest_end: latest_finish,
+		}
+	}
 	pub fn id(&self) -> JobId {
 		self.id
 	}
 	pub fn start(&self) -> Coord {
-		self.start.clone()
+		self.start
 	}
 	pub fn end(&self) -> Coord {
-		self.end.clone()
+		self.end
 	}
 	pub fn earliest_start(&self) -> TimeStep {
 		self.earliest_start
@@ -46,7 +66,7 @@ impl Job {
 
 impl PartialOrd for Job {
 	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-		Some(cmp_i32(self.earliest_start(), other.earliest_start()))
+		Some(self.cmp(other))
 	}
 }
 
@@ -67,23 +87,198 @@ impl Eq for Job {}
 impl Hash for Job {
 	fn hash<H: Hasher>(&self, state: &mut H) {
 		state.write_i32(self.id());
-		state.finish();
 	}
 }
 
 impl Debug for Job {
-	fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-		f.write_str(format!("jobID: {}", self.id()).as_str());
-		Ok(())
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		f.write_str(format!("jobID: {}", self.id()).as_str())
+	}
+}
+
+/// Parsed input, shared by every solver
+#[derive(Clone, Debug)]
+pub struct Problem {
+	num_rows: i32,
+	num_cols: i32,
+	num_vehicles: i32,
+	ride_bonus: i32,
+	max_tsteps: TimeStep,
+	jobs: Vec<Job>,
+}
+
+impl Problem {
+	pub fn new(input: FileReader) -> Result<Problem, Error> {
+		Problem::from_lines(&input.read_all_lines().map_err(Error::Read)?)
+	}
+
+	/// The header line followed by a line per ride, six numbers each. The header needs a positive
+	/// number of rows and columns, none of its other numbers negative, and as many rides as follow it
+	pub fn from_lines(lines: &[String]) -> Result<Problem, Error> {
+		let mut out = Problem {
+			num_rows: 0,
+			num_cols: 0,
+			num_vehicles: 0,
+			ride_bonus: 0,
+			max_tsteps: 0,
+			jobs: Vec::default(),
+		};
+		let mut num_rides = 0;
+
+		for (line_no, line) in lines.iter().enumerate() {
+			let splits = line
+				.split(' ')
+				.map(|s| s.parse::<i32>())
+				.collect::<Result<Vec<_>, _>>()
+				.ok()
+				.filter(|s| s.len() == 6)
+				.ok_or_else(|| Error::MalformedInput(line_no + 1, line.clone()))?;
+
+			if line_no == 0 {
+				if splits[0] <= 0 || splits[1] <= 0 || splits[2..].iter().any(|&n| n < 0) {
+					return Err(Error::MalformedInput(1, line.clone()));
+				}
+				out.num_rows = splits[0];
+				out.num_cols = splits[1];
+				out.num_vehicles = splits[2];
+				out.ride_bonus = splits[4];
+				out.max_tsteps = splits[5];
+				num_rides = splits[3] as usize;
+				// the count is only trusted once the rides are there
+				out.jobs = Vec::with_capacity(usize::min(num_rides, lines.len()));
+			} else {
+				let adjusted_line_no: i32 = line_no as i32 - 1; // ride numbers start at 0
+				let x_start = splits[0];
+				let y_start = splits[1];
+				let x_end = splits[2];
+				let y_end = splits[3];
+				let early = splits[4];
+				let late = splits[5];
+
+				out.jobs.push(Job {
+					id: adjusted_line_no,
+					start: Coord::new(x_start, y_start),
+					end: Coord::new(x_end, y_end),
+					earliest_start: early,
+					latest_end: late,
+				});
+			}
+		}
+
+		match lines.first() {
+			Some(_) if out.jobs.len() == num_rides => Ok(out),
+			Some(header) => Err(Error::MalformedInput(1, header.clone())),
+			None => Err(Error::MalformedInput(1, String::new())),
+		}
+	}
+
+	/// Jobs have to be ordered by ID, starting at 0
+	pub fn from_jobs(
+		num_rows: i32,
+		num_cols: i32,
+		num_vehicles: i32,
+		ride_bonus: i32,
+		max_tsteps: TimeStep,
+		jobs: Vec<Job>,
+	) -> Result<Problem, Error> {
+		if let Some((idx, j)) = jobs.iter().enumerate().find(|&(idx, j)| j.id() != idx as JobId) {
+			return Err(Error::MisnumberedJob(idx, j.id()));
+		}
+
+		Ok(Problem {
+			num_rows,
+			num_cols,
+			num_vehicles,
+			ride_bonus,
+			max_tsteps,
+			jobs,
+		})
+	}
+
+	pub fn num_rows(&self) -> i32 {
+		self.num_rows
+	}
+	pub fn num_cols(&self) -> i32 {
+		self.num_cols
+	}
+	pub fn num_vehicles(&self) -> i32 {
+		self.num_vehicles
+	}
+	pub fn num_jobs(&self) -> i32 {
+		self.jobs.len() as i32
+	}
+	pub fn ride_bonus(&self) -> i32 {
+		self.ride_bonus
+	}
+	pub fn max_tsteps(&self) -> TimeStep {
+		self.max_tsteps
+	}
+	/// Ordered by ID
+	pub fn jobs(&self) -> &[Job] {
+		&self.jobs
+	}
+	pub fn job(&self, id: JobId) -> &Job {
+		&self.jobs[id as usize]
 	}
 }
 
 type VehPtr = Rc<RefCell<Vehicle>>;
 
-/// Output of a single simulation timestep
+/// Nearest first, with equally near vehicles in random order
+fn shuffle_ties<'a, I>(nearest: I, rng: &mut Rng) -> Vec<VehPtr>
+where
+	I: Iterator<Item = (f64, &'a VehPtr)>,
+{
+	let mut out: Vec<VehPtr> = Vec::new();
+	let mut tie_start = 0;
+	let mut tie_dist = -1f64;
+
+	for (dist, v) in nearest {
+		if dist != tie_dist {
+			rng.shuffle(&mut out[tie_start..]);
+			tie_start = out.len();
+			tie_dist = dist;
+		}
+		out.push(v.clone());
+	}
+	rng.shuffle(&mut out[tie_start..]);
+
+	out
+}
+
+fn pooled_vehicles(tree: &KdTree<VehPtr, [f64; 2]>) -> Vec<VehPtr> {
+	// the tree doesn't expose its points directly, so walk it from the origin
+	let dist_measure = |a: &[f64], b: &[f64]| {
+		a.iter()
+			.zip(b.iter())
+			.map(|(x, y)| f64::abs(x - y))
+			.fold(0f64, ::std::ops::Add::add)
+	};
+
+	match tree.iter_nearest(&[0f64, 0f64], &dist_measure) {
+		Ok(itr) => itr.map(|(_, v)| v.clone()).collect(),
+		Err(_) => Vec::new(),
+	}
+}
+
+/// Points per step of taking the job for a vehicle that's free at `free_at`, counting the time spent getting to
+/// the start and waiting there, or None if it can't be finished on time
+fn points_per_step(free_at: TimeStep, pos: Coord, j: &Job, ride_bonus: f64) -> Option<f64> {
+	let arrival = free_at + pos.dist(&j.start());
+	let finish = i32::max(arrival, j.earliest_start()) + j.dist();
+	if finish >= j.latest_finish() {
+		return None;
+	}
+
+	let mut points = j.dist() as f64;
+	if arrival < j.earliest_start() {
+		points += ride_bonus;
+	}
+	Some(points / (finish - free_at) as f64)
+}
+
+/// Something a vehicle got done in a simulation timestep
 pub enum TickComplete {
-	/// No-op, nothing to report
-	Continue,
 	/// Vehicle began moving from the start
 	JobStart(
 		JobId, /*id*/
@@ -96,11 +291,58 @@ pub enum TickComplete {
 		TimeStep, /*latest_finish*/
 		Coord, /*end*/
 	),
+	/// Vehicle arrived at its new position
+	RepositionComplete(Coord /*pos*/),
+}
+
+/// What a vehicle was busy with
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SegmentKind {
+	/// Driving empty to the start of a job
+	Deadhead,
+	/// At the start of a job, waiting for its earliest start
+	Waiting,
+	/// Driving a job to its end
+	OnRide,
+	/// Moving, or lingering, without a job
+	Repositioning,
+}
+
+/// A single task of a vehicle, covering the simulation steps [from, to)
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Segment {
+	pub kind: SegmentKind,
+	pub job: Option<JobId>,
+	pub from: TimeStep,
+	pub to: TimeStep,
+}
+
+/// What to do with vehicles that have no suitable job
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RepositionPolicy {
+	/// Take late jobs, failing that stay at the last drop-off
+	Stay,
+	/// Never take late jobs, move toward jobs that become available within the given number of steps instead.
+	/// Vehicles also pass on a job that misses its bonus or pays less per step than driving to one of those jobs
+	/// and picking it up with the bonus. Vehicles with nowhere to go wait out the same number of steps before
+	/// looking for work again.
+	/// It doesn't pay off on the contest inputs: windows up to 200 steps score the same as `Stay` on e_high_bonus and
+	/// longer ones score less. Only d_metropolis gains a little, at 100 steps with `Funky`. `BonusAware` with a long
+	/// horizon leaves no vehicle idle while there's still a job it can reach, so it scores exactly the same
+	TowardDemand(TimeStep),
+}
+
+/// How jobs are matched to idle vehicles
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DispatchStrategy {
+	/// Earliest job first, nearest vehicle first, relaxing the start and end constraints until something fits
+	Funky,
+	/// Best points per step for every vehicle, where the ride bonus is scaled by `bonus_weight`.
+	/// Only jobs that become available within `horizon` steps are considered, and late jobs are never taken
+	BonusAware { bonus_weight: f64, horizon: TimeStep },
 }
 
 pub struct JobScheduler {
-	num_rows: i32,
-	num_cols: i32,
 	num_vehicles: i32,
 	num_jobs: i32,
 	ride_bonus: i32,
@@ -110,67 +352,39 @@ pub struct JobScheduler {
 	fleet: Vec<VehPtr>,
 	rem_jobs: Vec<Job>,
 	job_scores: HashMap<JobId, i32>,
+
+	dispatch_strategy: DispatchStrategy,
+	reposition_policy: RepositionPolicy,
+	rng: Option<Rng>,
+	/// Routes to follow instead of dispatching, next job first
+	fixed_routes: Option<Vec<VecDeque<JobId>>>,
+	book_ahead: bool,
+	dashboard: Option<Dashboard>,
+	observers: Vec<Box<dyn Observer>>,
 }
 
 impl JobScheduler {
-	pub fn new(input: FileReader) -> JobScheduler {
+	pub fn new(input: FileReader) -> Result<JobScheduler, Error> {
+		Ok(JobScheduler::from_problem(&Problem::new(input)?))
+	}
+
+	pub fn from_problem(problem: &Problem) -> JobScheduler {
 		let mut out = JobScheduler {
-			num_rows: 0,
-			num_cols: 0,
-			num_vehicles: 0,
-			num_jobs: 0,
-			ride_bonus: 0,
-			max_tsteps: 0,
+			num_vehicles: problem.num_vehicles(),
+			num_jobs: problem.num_jobs(),
+			ride_bonus: problem.ride_bonus(),
+			max_tsteps: problem.max_tsteps(),
 			current_step: 0,
-			fleet: Vec::default(),
-			rem_jobs: Vec::default(),
-			job_scores: HashMap::default(),
-		};
-
-		// parse input
-		match input.read_all_lines() {
-			Ok(lines) => {
-				for (line_no, line) in lines.iter().enumerate() {
-					let mut splits: Vec<i32> =
-						line.split(' ').map(|s| s.parse::<i32>().unwrap()).collect();
-					assert_eq!(splits.len(), 6);
-
-					if line_no == 0 {
-						out.num_rows = splits[0];
-						out.num_cols = splits[1];
-						out.num_vehicles = splits[2];
-						out.num_jobs = splits[3];
-						out.ride_bonus = splits[4];
-						out.max_tsteps = splits[5];
-
-						out.current_step = 0;
-						out.fleet = Vec::with_capacity(out.num_vehicles as usize);
-						out.rem_jobs = Vec::with_capacity(out.num_jobs as usize);
-						out.job_scores = HashMap::with_capacity(out.num_jobs as usize);
-					} else {
-						let adjusted_line_no: i32 = line_no as i32 - 1; // ride numbers start at 0
-						let x_start = splits[0];
-						let y_start = splits[1];
-						let x_end = splits[2];
-						let y_end = splits[3];
-						let early = splits[4];
-						let late = splits[5];
-
-						let jerb = Job {
-							id: adjusted_line_no,
-							start: Coord::new(x_start, y_start),
-							end: Coord::new(x_end, y_end),
-							earliest_start: early,
-							latest_end: late,
-						};
-
-						out.job_scores.insert(jerb.id(), 0);
-						out.rem_jobs.push(jerb);
-					}
-				}
-			}
-			Err(errs) => errs.into_iter()
-				.foreach(|err| print!("Error reading input. Error: {:?}", err)),
+			fleet: Vec::with_capacity(problem.num_vehicles() as usize),
+			rem_jobs: problem.jobs().to_vec(),
+			job_scores: problem.jobs().iter().map(|j| (j.id(), 0)).collect(),
+			dispatch_strategy: DispatchStrategy::Funky,
+			reposition_policy: RepositionPolicy::Stay,
+			rng: None,
+			fixed_routes: None,
+			book_ahead: false,
+			dashboard: None,
+			observers: vec![Box::new(SummaryPrinter)],
 		};
 
 		for i in 0..out.num_vehicles {
@@ -190,44 +404,139 @@ impl JobScheduler {
 		out
 	}
 
-	fn tick_vehicles(&mut self) -> KdTree<VehPtr, [f64; 2]> {
+	pub fn set_dispatch_strategy(&mut self, strategy: DispatchStrategy) {
+		self.dispatch_strategy = strategy;
+	}
+
+	pub fn set_reposition_policy(&mut self, policy: RepositionPolicy) {
+		self.reposition_policy = policy;
+	}
+
+	/// Break ties between equally near vehicles at random instead of in tree order
+	pub fn set_rng(&mut self, rng: Rng) {
+		self.rng = Some(rng);
+	}
+
+	pub fn seed(&self) -> Option<u64> {
+		self.rng.as_ref().map(|r| r.seed())
+	}
+
+	/// Replays the given routes instead of dispatching, every vehicle takes its next ride as
+	/// soon as it's free. The dispatch strategy and reposition policy are ignored
+	pub fn follow_solution(&mut self, solution: &Solution) {
+		self.fixed_routes = Some(
+			solution
+				.rides()
+				.iter()
+				.map(|r| r.iter().cloned().collect())
+				.collect(),
+		);
+	}
+
+	/// Offer vehicles to the dispatch strategy as soon as they pick up a job, as if they were
+	/// already at its end. The next job is then lined up and started right after the drop-off
+	pub fn set_book_ahead(&mut self, book_ahead: bool) {
+		self.book_ahead = book_ahead;
+	}
+
+	/// Redraw the dashboard as the simulation goes, and once more when it's over
+	pub fn set_dashboard(&mut self, dashboard: Dashboard) {
+		self.dashboard = Some(dashboard);
+	}
+
+	/// Observers are told about events in the order they were added. A `SummaryPrinter` is
+	/// registered when the scheduler is built
+	pub fn add_observer(&mut self, observer: Box<dyn Observer>) {
+		self.observers.push(observer);
+	}
+
+	/// Drops every observer added so far, the summary printer included
+	pub fn clear_observers(&mut self) {
+		self.observers.clear();
+	}
+
+	fn notify<F>(&mut self, mut callback: F)
+	where
+		F: FnMut(&mut dyn Observer, &JobScheduler),
+	{
+		let mut observers = mem::take(&mut self.observers);
+		for o in observers.iter_mut() {
+			callback(o.as_mut(), self);
+		}
+		self.observers = observers;
+	}
+
+	fn tick_vehicles(&mut self) -> Result<KdTree<VehPtr, [f64; 2]>, Error> {
 		let mut bounding_tree = KdTree::new(2);
+		// vehicle, job and whether it started on time or arrived on time, reported once all vehicles moved
+		let mut started: Vec<(usize, JobId, bool)> = Vec::new();
+		let mut completed: Vec<(usize, JobId, bool)> = Vec::new();
 
 		for v in self.fleet.iter_mut() {
 			if self.current_step == 1 {
 				// all vehicles are idle in the first tick
-				let coord = v.borrow().current_pos().unwrap();
-				bounding_tree.add([coord.x as f64, coord.y as f64], v.clone());
+				let coord = v.borrow().position();
+				bounding_tree.add([coord.x as f64, coord.y as f64], v.clone())?;
 				continue;
 			}
 
-			let result = v.borrow_mut().tick(self.current_step);
-			match result {
-				TickComplete::Continue => {}
-				TickComplete::JobStart(id, dist, earliest_start) => {
-					// save the negative score for easy exclusion later
-					let score =
-						-(dist + (self.ride_bonus * (self.current_step == earliest_start) as i32));
-					self.job_scores.insert(id, score);
-				}
-				TickComplete::JobComplete(id, latest_finish, coord) => {
-					// flip the sign on the score if it arrives on time
-					if self.current_step < latest_finish {
-						let score = self.job_scores[&id];
-						self.job_scores.insert(id, -score);
+			let results = v.borrow_mut().tick(self.current_step)?;
+			for result in results {
+				match result {
+					TickComplete::JobStart(id, dist, earliest_start) => {
+						// save the negative score for easy exclusion later
+						let bonus = self.current_step == earliest_start;
+						let score = -(dist + (self.ride_bonus * bonus as i32));
+						self.job_scores.insert(id, score);
+						started.push((v.borrow().id() as usize, id, bonus));
+
+						// vehicles that finish the job within the step are offered once it's complete
+						if self.book_ahead && !v.borrow().is_idle() {
+							if let Some((_, coord)) = v.borrow().availability(self.current_step) {
+								bounding_tree.add([coord.x as f64, coord.y as f64], v.clone())?;
+							}
+						}
 					}
+					TickComplete::JobComplete(id, latest_finish, coord) => {
+						// flip the sign on the score if it arrives on time
+						let on_time = self.current_step < latest_finish;
+						if on_time {
+							let score = self.job_scores[&id];
+							self.job_scores.insert(id, -score);
+						}
+						completed.push((v.borrow().id() as usize, id, on_time));
 
-					bounding_tree.add([coord.x as f64, coord.y as f64], v.clone());
-					//println!("Vehicle {} completed job", v.borrow().id());
-				}
-			};
+						// unless it has its next job lined up already
+						if v.borrow().is_idle() {
+							bounding_tree.add([coord.x as f64, coord.y as f64], v.clone())?;
+						}
+						trace!("Step {} | Vehicle {} completed job {}", self.current_step, v.borrow().id(), id);
+					}
+					TickComplete::RepositionComplete(coord) => {
+						bounding_tree.add([coord.x as f64, coord.y as f64], v.clone())?;
+					}
+				};
+			}
+		}
+
+		let step = self.current_step;
+		for (vehicle, job, bonus) in started {
+			self.notify(|o, _| o.on_job_start(step, vehicle, job, bonus));
+		}
+		for (vehicle, job, on_time) in completed {
+			self.notify(|o, _| o.on_job_complete(step, vehicle, job, on_time));
 		}
 
-		bounding_tree
+		Ok(bounding_tree)
 	}
 
-	fn funky_scheduling(&mut self, idle_vehicles: &KdTree<VehPtr, [f64; 2]>) {
+	fn funky_scheduling(&mut self, idle_vehicles: &KdTree<VehPtr, [f64; 2]>) -> Result<(), Error> {
+		let mut rng = self.rng.take();
+		let mut result = Ok(());
+
 		if idle_vehicles.size() > 0 {
+			let ride_bonus = self.ride_bonus as f64;
+			let mut held = HashSet::new();
 			let mut candidates: Vec<VehPtr> = Vec::new();
 			let mut relax_start = false;
 			let mut relax_end = false;
@@ -246,36 +555,51 @@ impl JobScheduler {
 							.fold(0f64, ::std::ops::Add::add)
 					};
 
-					'nearest_loop: for mut itr in idle_vehicles.iter_nearest(
+					if let Ok(itr) = idle_vehicles.iter_nearest(
 						vec![start.x as f64, start.y as f64].as_slice(),
 						&dist_measure,
 					) {
-						while let Some(&mut (dist_from_start, v)) = itr.next().as_mut() {
-							if v.borrow().is_idle() {
+						let nearest: Box<dyn Iterator<Item = VehPtr>> = match rng {
+							Some(ref mut rng) => Box::new(shuffle_ties(itr, rng).into_iter()),
+							None => Box::new(itr.map(|(_, v)| v.clone())),
+						};
+
+						for v in nearest {
+							if held.contains(&v.borrow().id()) {
+								continue;
+							}
+
+							let availability = v.borrow().availability(self.current_step);
+							if let Some((free_at, pos)) = availability {
 								candidates.push(v.clone());
 
-								let pos = v.borrow().current_pos().unwrap();
 								let dist_to_start = pos.dist(&j.start());
 								let tot_dist = dist_to_start + j.dist();
 
-								if relax_end || self.current_step + tot_dist < j.latest_finish() {
-									if relax_start
-										|| self.current_step + dist_to_start < j.earliest_start()
-										{
-											assignee = Some(v.clone());
-											assigned_idx = idx as i32;
-											break 'job_loop;
-										}
+								if (relax_end || free_at + tot_dist < j.latest_finish())
+									&& (relax_start || free_at + dist_to_start < j.earliest_start())
+								{
+									let rate = points_per_step(free_at, pos, j, ride_bonus).unwrap_or(0f64);
+									if self.worth_waiting(free_at, pos, rate, ride_bonus) {
+										held.insert(v.borrow().id());
+										continue;
+									}
+
+									assignee = Some(v.clone());
+									assigned_idx = idx as i32;
+									break 'job_loop;
 								}
 							}
 						}
 					}
 				}
 
-				if assigned_idx != -1 {
+				if let Some(assignee) = assignee {
 					let assigned = self.rem_jobs.remove(assigned_idx as usize);
-					let assignee = assignee.unwrap();
-					assignee.borrow_mut().queue_new_job(assigned);
+					if let Err(e) = self.assign(&assignee, assigned) {
+						result = Err(e);
+						break 'assign_loop;
+					}
 
 					relax_start = false;
 					relax_end = false;
@@ -284,36 +608,279 @@ impl JobScheduler {
 					if !relax_start || !relax_end {
 						if !relax_start {
 							relax_start = true;
-						} else {
+						} else if self.reposition_policy == RepositionPolicy::Stay {
 							relax_end = true;
+						} else {
+							// late jobs aren't worth anything, leave the rest for repositioning
+							break 'assign_loop;
 						}
 					} else {
-						unreachable!();
+						// shouldn't happen, with both relaxed the first candidate takes the job
+						result = Err(Error::DispatchFailed("no vehicle took a job with all conditions relaxed"));
+						break 'assign_loop;
 					}
 				} else {
 					break 'assign_loop;
 				}
 			}
 		}
+
+		// put the generator back either way
+		self.rng = rng;
+		result
 	}
 
-	pub fn run(&mut self) {
-		assert_eq!(self.rem_jobs.is_empty(), false);
+	fn bonus_aware_scheduling(
+		&mut self,
+		idle_vehicles: &KdTree<VehPtr, [f64; 2]>,
+		bonus_weight: f64,
+		horizon: TimeStep,
+	) -> Result<(), Error> {
+		let current_step = self.current_step;
+		let ride_bonus = self.ride_bonus as f64 * bonus_weight;
+		let mut vehicles = pooled_vehicles(idle_vehicles);
+
+		while !vehicles.is_empty() {
+			let mut best: Option<(f64, usize, usize)> = None;
+
+			for (v_idx, v) in vehicles.iter().enumerate() {
+				let (free_at, pos) = match v.borrow().availability(current_step) {
+					Some(a) => a,
+					None => continue,
+				};
+
+				for (j_idx, j) in self.rem_jobs
+					.iter()
+					.enumerate()
+					.take_while(|&(_, j)| j.earliest_start() <= current_step + horizon)
+				{
+					// waiting for the bonus is fine as long as it pays for the time spent
+					if let Some(rate) = points_per_step(free_at, pos, j, ride_bonus) {
+						if best.is_none_or(|(r, _, _)| rate > r) {
+							best = Some((rate, v_idx, j_idx));
+						}
+					}
+				}
+			}
 
-		println!(
-			"Being Simulation | Vehicles: {} | Jobs: {} | Ticks: {}",
-			self.num_vehicles, self.num_jobs, self.max_tsteps
-		);
+			match best {
+				Some((rate, v_idx, j_idx)) => {
+					let assignee = vehicles.swap_remove(v_idx);
+					let (free_at, pos) = assignee.borrow().availability(current_step).unwrap();
+					if self.worth_waiting(free_at, pos, rate, ride_bonus) {
+						// leave it for repositioning
+						continue;
+					}
+
+					let assigned = self.rem_jobs.remove(j_idx);
+					self.assign(&assignee, assigned)?;
+				}
+				None => break,
+			}
+		}
+
+		Ok(())
+	}
+
+	fn assign(&mut self, assignee: &VehPtr, job: Job) -> Result<(), Error> {
+		let (step, vehicle, id) = (self.current_step, assignee.borrow().id(), job.id());
+		debug!("Step {} | Vehicle {} -> Job {}", step, vehicle, id);
+		assignee.borrow_mut().queue_new_job(job)?;
+		self.notify(|o, _| o.on_assign(step, vehicle as usize, id));
+		Ok(())
+	}
+
+	fn follow_routes(&mut self) -> Result<(), Error> {
+		let mut routes = match self.fixed_routes.take() {
+			Some(r) => r,
+			None => return Ok(()),
+		};
+
+		let fleet = self.fleet.clone();
+		for (v, route) in fleet.iter().zip(routes.iter_mut()) {
+			if !v.borrow().is_idle() {
+				continue;
+			}
+
+			if let Some(id) = route.pop_front() {
+				if let Some(idx) = self.rem_jobs.iter().position(|j| j.id() == id) {
+					let job = self.rem_jobs.remove(idx);
+					let assigned = self.assign(v, job);
+					if assigned.is_err() {
+						self.fixed_routes = Some(routes);
+						return assigned;
+					}
+				}
+			}
+		}
+
+		self.fixed_routes = Some(routes);
+		Ok(())
+	}
+
+	fn dispatch(&mut self, idle_vehicles: &KdTree<VehPtr, [f64; 2]>) -> Result<(), Error> {
+		if self.fixed_routes.is_some() {
+			return self.follow_routes();
+		}
+
+		match self.dispatch_strategy {
+			DispatchStrategy::Funky => self.funky_scheduling(idle_vehicles)?,
+			DispatchStrategy::BonusAware {
+				bonus_weight,
+				horizon,
+			} => self.bonus_aware_scheduling(idle_vehicles, bonus_weight, horizon)?,
+		}
+
+		if let RepositionPolicy::TowardDemand(window) = self.reposition_policy {
+			self.reposition_idle_vehicles(window)?;
+		}
+		Ok(())
+	}
+
+	/// The upcoming job within the repositioning window that pays the most per step when the vehicle heads
+	/// straight for it and picks it up with the bonus, along with that rate
+	fn best_bonus_job(
+		&self,
+		free_at: TimeStep,
+		pos: Coord,
+		window: TimeStep,
+		ride_bonus: f64,
+		claimed: &HashSet<JobId>,
+	) -> Option<(f64, &Job)> {
+		let current_step = self.current_step;
+		self.rem_jobs
+			.iter()
+			.take_while(|j| j.earliest_start() <= current_step + window)
+			.filter(|j| free_at + pos.dist(&j.start()) < j.earliest_start() && !claimed.contains(&j.id()))
+			.filter_map(|j| points_per_step(free_at, pos, j, ride_bonus).map(|rate| (rate, j)))
+			.fold(None, |best: Option<(f64, &Job)>, (rate, j)| match best {
+				Some((r, _)) if r >= rate => best,
+				_ => Some((rate, j)),
+			})
+	}
 
-		for step in 1..self.max_tsteps {
-			self.current_step = step;
+	/// Whether a vehicle does better repositioning for a bonus than taking a job worth `rate` points per step
+	fn worth_waiting(&self, free_at: TimeStep, pos: Coord, rate: f64, ride_bonus: f64) -> bool {
+		match self.reposition_policy {
+			RepositionPolicy::Stay => false,
+			RepositionPolicy::TowardDemand(window) => self
+				.best_bonus_job(free_at, pos, window, ride_bonus, &HashSet::new())
+				.is_some_and(|(r, _)| r > rate),
+		}
+	}
 
-			let idle_vehicles = self.tick_vehicles();
-			self.funky_scheduling(&idle_vehicles);
+	fn reposition_idle_vehicles(&mut self, window: TimeStep) -> Result<(), Error> {
+		let current_step = self.current_step;
+		let ride_bonus = self.ride_bonus as f64;
+		let mut claimed = HashSet::new();
+
+		// jobs that can't be finished on time anymore will never be assigned
+		self.rem_jobs
+			.retain(|j| current_step + j.dist() < j.latest_finish());
+
+		for v in self.fleet.iter().filter(|v| v.borrow().is_idle()) {
+			let pos = v.borrow().position();
+			// head for the best job that can still be picked up with the bonus, failing that the nearest one
+			let target = self
+				.best_bonus_job(current_step, pos, window, ride_bonus, &claimed)
+				.map(|(_, j)| j)
+				.or_else(|| {
+					self.rem_jobs
+						.iter()
+						.take_while(|j| j.earliest_start() <= current_step + window)
+						.filter(|j| j.earliest_start() > current_step && !claimed.contains(&j.id()))
+						.min_by_key(|j| pos.dist(&j.start()))
+				})
+				.map(|j| (j.id(), j.start()));
+
+			let (dest, min_steps) = match target {
+				Some((id, start)) => {
+					claimed.insert(id);
+					(start, 1)
+				}
+				None => (pos, i32::max(window, 1)),
+			};
+
+			let steps = v.borrow_mut().reposition(dest, min_steps)?;
+			trace!(
+				"Step {} | Vehicle {} -> Repositioning to ({}, {}), Steps {}",
+				current_step,
+				v.borrow().id(),
+				dest.x,
+				dest.y,
+				steps
+			);
 		}
 
-		println!(
-			"End Simulation | Remaining jobs: {} | Idling Vehicles: {} | Score: {}",
+		Ok(())
+	}
+
+	/// Advances the simulation by a single step, false once it's over
+	pub fn step(&mut self) -> Result<bool, Error> {
+		if self.current_step + 1 >= self.max_tsteps {
+			return Ok(false);
+		}
+		self.current_step += 1;
+
+		let step = self.current_step;
+		self.notify(|o, _| o.on_tick(step));
+
+		let idle_vehicles = self.tick_vehicles()?;
+		self.dispatch(&idle_vehicles)?;
+
+		if self.dashboard.as_ref().is_some_and(|d| d.is_due()) {
+			self.draw_dashboard();
+		}
+		Ok(true)
+	}
+
+	fn draw_dashboard(&mut self) {
+		let mut dashboard = match self.dashboard.take() {
+			Some(d) => d,
+			None => return,
+		};
+
+		let idle = self.fleet.iter().filter(|v| v.borrow().is_idle()).count();
+		let status = Status {
+			step: self.current_step,
+			max_tsteps: self.max_tsteps,
+			busy: self.fleet.len() - idle,
+			idle,
+			remaining_jobs: self.rem_jobs.len(),
+			score: self.calculate_score(),
+			positions: if dashboard.wants_positions() {
+				self.positions()
+			} else {
+				Vec::new()
+			},
+		};
+		dashboard.draw(&status);
+
+		self.dashboard = Some(dashboard);
+	}
+
+	pub fn current_step(&self) -> TimeStep {
+		self.current_step
+	}
+
+	/// Simulates every remaining step. Stops at the first error, leaving the simulation where it went wrong
+	pub fn run(&mut self) -> Result<(), Error> {
+		if self.rem_jobs.is_empty() {
+			return Err(Error::NoJobs);
+		}
+
+		self.notify(|o, scheduler| o.on_start(scheduler));
+
+		while self.step()? {}
+		self.draw_dashboard();
+
+		self.notify(|o, scheduler| o.on_end(scheduler));
+		Ok(())
+	}
+
+	pub fn summary(&self) -> String {
+		let mut out = format!(
+			"Remaining jobs: {} | Idling Vehicles: {} | Score: {}",
 			self.rem_jobs.len(),
 			self.fleet
 				.iter()
@@ -322,36 +889,51 @@ impl JobScheduler {
 				.len(),
 			self.calculate_score()
 		);
+
+		if let Some(seed) = self.seed() {
+			out += &format!(" | Seed: {}", seed);
+		}
+
+		out
 	}
 
-	pub fn write_output(&self, out: &mut FileWriter) {
-		out.write_line(&self.output_as_str());
+	/// Writes the solution so far to a `FileWriter`, or any other writer
+	pub fn write_output<W: Write>(&self, out: &mut W) -> Result<(), Error> {
+		Ok(self.solution().write_to(out)?)
 	}
 
 	pub fn output_as_str(&self) -> String {
-		let mut out = String::new();
-		for (idx, v) in self.fleet.iter().enumerate() {
-			let v = v.borrow();
-			assert_eq!(v.id(), idx as i32);
-
-			let rides = v.assigned_rides();
-			out += &format!(
-				"{}{}\n",
-				rides.len(),
-				rides
-					.iter()
-					.fold(String::new(), |s, id| s + " " + id.to_string().as_str())
-					.as_str()
-			);
-		}
+		self.solution().output_as_str()
+	}
 
-		out
+	pub fn solution(&self) -> Solution {
+		// by vehicle ID, whatever order the fleet is in
+		let mut rides = self.fleet
+			.iter()
+			.map(|v| (v.borrow().id(), v.borrow().assigned_rides()))
+			.collect_vec();
+		rides.sort_by_key(|&(id, _)| id);
+
+		Solution::new(rides.into_iter().map(|(_, r)| r).collect())
+	}
+
+	/// What every vehicle has done so far, by vehicle ID
+	pub fn segments(&self) -> Vec<Vec<Segment>> {
+		self.fleet.iter().map(|v| v.borrow().segments()).collect()
+	}
+
+	/// Where every vehicle is once it's moved in the current step, by vehicle ID, which is where
+	/// `Solution::positions` puts it at `current_step() + 1` as long as the simulation keeps to the
+	/// scorer's timing. Vehicles get their first ride in step 1 and only start moving in step 2 though,
+	/// two steps later than the scorer has them set off, and only make that up by waiting for a ride's
+	/// earliest start. Vehicles between intersections are placed along their way, going along x first
+	pub fn positions(&self) -> Vec<Coord> {
+		self.fleet.iter().map(|v| v.borrow().position()).collect()
 	}
 
 	pub fn calculate_score(&self) -> u64 {
 		self.job_scores
 			.values()
-			.into_iter()
 			.fold(0, |a, s| if *s > 0 { a + *s as u64 } else { a })
 	}
 }
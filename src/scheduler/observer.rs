@@ -0,0 +1,39 @@
+use super::{JobId, JobScheduler};
+use util::TimeStep;
+
+/// Hooks into a running simulation, for metrics and the like. Every callback does nothing by default.
+/// Vehicles are given by ID, steps are the simulation step the event happened in
+pub trait Observer {
+	fn on_start(&mut self, _scheduler: &JobScheduler) {}
+
+	/// Before any vehicle moves in the step
+	fn on_tick(&mut self, _step: TimeStep) {}
+
+	/// The job is lined up for the vehicle, which starts on it in the next step. With book-ahead this also
+	/// fires for busy vehicles, in the step they pick up their current job, and they start on the new one once
+	/// they've dropped that one off
+	fn on_assign(&mut self, _step: TimeStep, _vehicle: usize, _job: JobId) {}
+
+	/// The vehicle picked up the job, right at its earliest start if `bonus`
+	fn on_job_start(&mut self, _step: TimeStep, _vehicle: usize, _job: JobId, _bonus: bool) {}
+
+	fn on_job_complete(&mut self, _step: TimeStep, _vehicle: usize, _job: JobId, _on_time: bool) {}
+
+	fn on_end(&mut self, _scheduler: &JobScheduler) {}
+}
+
+/// Logs a line when the simulation begins and its summary when it ends, at info level
+pub struct SummaryPrinter;
+
+impl Observer for SummaryPrinter {
+	fn on_start(&mut self, scheduler: &JobScheduler) {
+		info!(
+			"Being Simulation | Vehicles: {} | Jobs: {} | Ticks: {}",
+			scheduler.num_vehicles, scheduler.num_jobs, scheduler.max_tsteps
+		);
+	}
+
+	fn on_end(&mut self, scheduler: &JobScheduler) {
+		info!("End Simulation | {}", scheduler.summary());
+	}
+}
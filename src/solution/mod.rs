@@ -0,0 +1,238 @@
+use scheduler::{JobId, Problem};
+use std::collections::HashSet;
+use std::error;
+use std::fmt;
+use std::io::Write;
+use util::{write_atomically, Coord, FileIOError, FileReader, TimeStep};
+
+#[derive(Debug, PartialEq)]
+pub enum SolutionError {
+	/// Expected, found
+	VehicleCountMismatch(usize, usize),
+	UnknownRide(JobId),
+	DuplicateRide(JobId),
+	/// Line number, starting at 1, of a line that isn't a ride count followed by that many rides
+	MalformedLine(usize),
+}
+
+impl fmt::Display for SolutionError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			SolutionError::VehicleCountMismatch(expected, found) => {
+				write!(f, "expected routes for {} vehicles, found {}", expected, found)
+			}
+			SolutionError::UnknownRide(id) => write!(f, "ride {} doesn't exist", id),
+			SolutionError::DuplicateRide(id) => write!(f, "ride {} is served more than once", id),
+			SolutionError::MalformedLine(line_no) => write!(f, "malformed route on line {}", line_no),
+		}
+	}
+}
+
+impl error::Error for SolutionError {}
+
+/// What `Solution::write_unless_worse` did
+#[derive(Debug, PartialEq)]
+pub enum WriteOutcome {
+	Written,
+	/// The file already there scores at least as much, with this score, and was left alone
+	Kept(u64),
+}
+
+/// Ride assignments for the whole fleet, indexed by vehicle ID
+#[derive(Clone, Debug, PartialEq)]
+pub struct Solution {
+	rides: Vec<Vec<JobId>>,
+}
+
+impl Solution {
+	pub fn new(rides: Vec<Vec<JobId>>) -> Solution {
+		Solution { rides }
+	}
+
+	pub fn rides(&self) -> &[Vec<JobId>] {
+		&self.rides
+	}
+
+	pub fn into_rides(self) -> Vec<Vec<JobId>> {
+		self.rides
+	}
+
+	/// Reads the submission format written by `output_as_str`, a line per vehicle
+	pub fn from_lines(lines: &[String]) -> Result<Solution, SolutionError> {
+		let mut rides = Vec::new();
+		for (line_no, line) in lines.iter().enumerate().filter(|&(_, l)| !l.trim().is_empty()) {
+			let numbers = line
+				.split_whitespace()
+				.map(|t| t.parse::<JobId>())
+				.collect::<Result<Vec<_>, _>>()
+				.map_err(|_| SolutionError::MalformedLine(line_no + 1))?;
+
+			if numbers[0] < 0 || numbers[0] as usize != numbers.len() - 1 {
+				return Err(SolutionError::MalformedLine(line_no + 1));
+			}
+			rides.push(numbers[1..].to_vec());
+		}
+
+		Ok(Solution { rides })
+	}
+
+	/// Writes the submission format to a `FileWriter`, or any other writer
+	pub fn write_to<W: Write>(&self, out: &mut W) -> Result<(), FileIOError> {
+		out.write_all(self.output_as_str().as_bytes())
+			.map_err(FileIOError::LineWriteError)
+	}
+
+	/// Replaces the file in one go, see `write_atomically`
+	pub fn write(&self, path: &str) -> Result<(), FileIOError> {
+		write_atomically(path, &self.output_as_str())
+	}
+
+	/// Like `write`, but leaves the file alone if it holds a valid solution that scores at least
+	/// as much. Files that can't be read or scored are replaced
+	pub fn write_unless_worse(&self, problem: &Problem, path: &str) -> Result<WriteOutcome, FileIOError> {
+		let existing = FileReader::new(path)
+			.ok()
+			.and_then(|r| r.read_all_lines().ok())
+			.and_then(|lines| Solution::from_lines(&lines).ok())
+			.and_then(|s| s.score(problem).ok());
+
+		match existing {
+			Some(score) if score >= self.score(problem).unwrap_or(0) => Ok(WriteOutcome::Kept(score)),
+			_ => self.write(path).map(|_| WriteOutcome::Written),
+		}
+	}
+
+	pub fn output_as_str(&self) -> String {
+		let mut out = String::new();
+		for rides in self.rides.iter() {
+			out += &format!(
+				"{}{}\n",
+				rides.len(),
+				rides
+					.iter()
+					.fold(String::new(), |s, id| s + " " + id.to_string().as_str())
+					.as_str()
+			);
+		}
+
+		out
+	}
+
+	pub fn validate(&self, problem: &Problem) -> Result<(), SolutionError> {
+		if self.rides.len() != problem.num_vehicles() as usize {
+			return Err(SolutionError::VehicleCountMismatch(
+				problem.num_vehicles() as usize,
+				self.rides.len(),
+			));
+		}
+
+		let mut seen = HashSet::new();
+		for &id in self.rides.iter().flat_map(|r| r.iter()) {
+			if id < 0 || id >= problem.num_jobs() {
+				return Err(SolutionError::UnknownRide(id));
+			} else if !seen.insert(id) {
+				return Err(SolutionError::DuplicateRide(id));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Where every vehicle is at the given step, see `route_position`. Invalid solutions are rejected
+	pub fn positions(&self, problem: &Problem, step: TimeStep) -> Result<Vec<Coord>, SolutionError> {
+		self.validate(problem)?;
+
+		Ok(self.rides.iter().map(|r| route_position(problem, r, step)).collect())
+	}
+
+	/// Score according to the contest rules, invalid solutions are rejected
+	pub fn score(&self, problem: &Problem) -> Result<u64, SolutionError> {
+		self.validate(problem)?;
+
+		Ok(self.rides
+			.iter()
+			.map(|r| route_score(problem, r))
+			.sum())
+	}
+}
+
+/// Timing of a single ride on a vehicle's route
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RideTiming {
+	pub id: JobId,
+	pub departure: TimeStep,
+	pub arrival: TimeStep,
+	pub start: TimeStep,
+	pub finish: TimeStep,
+	pub score: u64,
+}
+
+impl RideTiming {
+	pub fn on_time(&self) -> bool {
+		self.score > 0
+	}
+}
+
+/// Replays a vehicle's route from the origin, one entry per ride
+pub fn route_timings(problem: &Problem, rides: &[JobId]) -> Vec<RideTiming> {
+	let mut pos = Coord::default();
+	let mut now: TimeStep = 0;
+
+	rides
+		.iter()
+		.map(|&id| {
+			let job = problem.job(id);
+			let departure = now;
+			let arrival = now + pos.dist(&job.start());
+			let start = i32::max(arrival, job.earliest_start());
+			let finish = start + job.dist();
+
+			let score = if finish <= job.latest_finish() && finish <= problem.max_tsteps() {
+				let bonus = if start == job.earliest_start() {
+					problem.ride_bonus()
+				} else {
+					0
+				};
+				(job.dist() + bonus) as u64
+			} else {
+				0
+			};
+
+			pos = job.end();
+			now = finish;
+
+			RideTiming {
+				id,
+				departure,
+				arrival,
+				start,
+				finish,
+				score,
+			}
+		})
+		.collect()
+}
+
+pub fn route_score(problem: &Problem, rides: &[JobId]) -> u64 {
+	route_timings(problem, rides).iter().map(|t| t.score).sum()
+}
+
+/// Intersection a vehicle driving the route is at after the given number of steps, timed like
+/// the scorer does. Between intersections it drives along x first, then along y (see
+/// `Coord::towards`), and it stays where its last ride ends
+pub fn route_position(problem: &Problem, rides: &[JobId], step: TimeStep) -> Coord {
+	let mut pos = Coord::default();
+	for t in route_timings(problem, rides) {
+		let job = problem.job(t.id);
+		if step < t.arrival {
+			return pos.towards(&job.start(), step - t.departure);
+		} else if step < t.start {
+			return job.start();
+		} else if step < t.finish {
+			return job.start().towards(&job.end(), step - t.start);
+		}
+		pos = job.end();
+	}
+
+	pos
+}
@@ -0,0 +1,188 @@
+use scheduler::{JobId, Problem};
+use solution::Solution;
+use std::time::{Duration, Instant};
+use util::{Coord, TimeStep};
+
+#[derive(Clone, Debug)]
+pub struct ExactParams {
+	/// Instances with more rides than this are refused
+	pub max_rides: usize,
+	pub time_limit: Option<Duration>,
+}
+
+impl Default for ExactParams {
+	fn default() -> Self {
+		ExactParams {
+			max_rides: 12,
+			time_limit: Some(Duration::from_secs(10)),
+		}
+	}
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ExactError {
+	/// Rides, limit
+	TooLarge(usize, usize),
+	/// Ran out of time, with the best solution found until then
+	TimedOut(Solution),
+}
+
+#[derive(Copy, Clone)]
+struct VehicleState {
+	pos: Coord,
+	free_at: TimeStep,
+}
+
+struct Search<'a> {
+	problem: &'a Problem,
+	deadline: Option<Instant>,
+	nodes: u64,
+	timed_out: bool,
+	vehicles: Vec<VehicleState>,
+	routes: Vec<Vec<JobId>>,
+	served: Vec<bool>,
+	score: u64,
+	best_score: u64,
+	best: Vec<Vec<JobId>>,
+}
+
+impl<'a> Search<'a> {
+	/// Start and finish of the ride if the vehicle took it next, None if it would be late
+	fn timing(&self, state: &VehicleState, ride: JobId) -> Option<(TimeStep, TimeStep)> {
+		let job = self.problem.job(ride);
+		let start = TimeStep::max(state.free_at + state.pos.dist(&job.start()), job.earliest_start());
+		let finish = start + job.dist();
+		if finish > job.latest_finish() || finish > self.problem.max_tsteps() {
+			None
+		} else {
+			Some((start, finish))
+		}
+	}
+
+	fn points(&self, ride: JobId, start: TimeStep) -> u64 {
+		let job = self.problem.job(ride);
+		job.dist() as u64
+			+ if start == job.earliest_start() {
+				self.problem.ride_bonus() as u64
+			} else {
+				0
+			}
+	}
+
+	/// Every unserved ride some vehicle could still take on time, with the bonus if any could make it
+	fn upper_bound(&self) -> u64 {
+		let mut bound = self.score;
+		for ride in 0..self.served.len() {
+			if self.served[ride] {
+				continue;
+			}
+
+			let ride = ride as JobId;
+			let best = self
+				.vehicles
+				.iter()
+				.filter_map(|v| self.timing(v, ride).map(|(start, _)| self.points(ride, start)))
+				.max();
+			bound += best.unwrap_or(0);
+		}
+
+		bound
+	}
+
+	/// Appends rides in order of (start, vehicle), so that every set of routes is visited once.
+	/// Only the first vehicle without rides is tried, the others are interchangeable with it
+	fn branch(&mut self, last: (TimeStep, usize)) {
+		self.nodes += 1;
+		if self.nodes.is_multiple_of(4096) && self.deadline.is_some_and(|d| Instant::now() > d) {
+			self.timed_out = true;
+		}
+		if self.timed_out {
+			return;
+		}
+
+		if self.score > self.best_score {
+			self.best_score = self.score;
+			self.best = self.routes.clone();
+		}
+		if self.upper_bound() <= self.best_score {
+			return;
+		}
+
+		let first_empty = self.routes.iter().position(|r| r.is_empty());
+		for v in 0..self.vehicles.len() {
+			if self.routes[v].is_empty() && Some(v) != first_empty {
+				continue;
+			}
+
+			for ride in 0..self.served.len() {
+				if self.served[ride] {
+					continue;
+				}
+
+				let ride = ride as JobId;
+				let state = self.vehicles[v];
+				let (start, finish) = match self.timing(&state, ride) {
+					Some(t) => t,
+					None => continue,
+				};
+				if (start, v) < last {
+					continue;
+				}
+
+				let points = self.points(ride, start);
+				self.served[ride as usize] = true;
+				self.routes[v].push(ride);
+				self.vehicles[v] = VehicleState {
+					pos: self.problem.job(ride).end(),
+					free_at: finish,
+				};
+				self.score += points;
+
+				self.branch((start, v));
+
+				self.score -= points;
+				self.vehicles[v] = state;
+				self.routes[v].pop();
+				self.served[ride as usize] = false;
+			}
+		}
+	}
+}
+
+/// Proves the best possible score by branch and bound, only feasible for a handful of rides
+pub fn exact(problem: &Problem, params: &ExactParams) -> Result<Solution, ExactError> {
+	let num_rides = problem.num_jobs() as usize;
+	if num_rides > params.max_rides {
+		return Err(ExactError::TooLarge(num_rides, params.max_rides));
+	}
+
+	// more vehicles than rides can never help
+	let num_vehicles = usize::min(problem.num_vehicles() as usize, num_rides);
+	let mut search = Search {
+		problem,
+		deadline: params.time_limit.map(|t| Instant::now() + t),
+		nodes: 0,
+		timed_out: false,
+		vehicles: vec![
+			VehicleState {
+				pos: Coord::default(),
+				free_at: 0,
+			};
+			num_vehicles
+		],
+		routes: vec![Vec::new(); num_vehicles],
+		served: vec![false; num_rides],
+		score: 0,
+		best_score: 0,
+		best: vec![Vec::new(); num_vehicles],
+	};
+	search.branch((0, 0));
+
+	let mut rides = search.best;
+	rides.resize(problem.num_vehicles() as usize, Vec::new());
+	if search.timed_out {
+		Err(ExactError::TimedOut(Solution::new(rides)))
+	} else {
+		Ok(Solution::new(rides))
+	}
+}
@@ -0,0 +1,276 @@
+use rng::Rng;
+use scheduler::{DispatchStrategy, JobId, Problem};
+use solution::{Solution, SolutionError};
+use solver::routes::Routes;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// How two parents are combined, both keep whole routes of a parent intact
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Crossover {
+	/// Every vehicle takes its route from either parent with equal odds
+	VehicleSwap,
+	/// A few routes of the second parent are copied over the first parent's
+	/// vehicles they overlap the most with
+	RouteBased,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Mutation {
+	/// One ride moves to its best position anywhere
+	Relocate,
+	/// Two rides on different vehicles trade places
+	Swap,
+	/// Rides starting around a random one are taken out and put back
+	Ruin,
+}
+
+#[derive(Clone, Debug)]
+pub struct GaParams {
+	/// Strategy for the greedy schedule the population is grown from
+	pub initial: DispatchStrategy,
+	pub population: usize,
+	pub generations: usize,
+	/// Stop early once this much time has passed
+	pub time_limit: Option<Duration>,
+	/// Best individuals carried over unchanged to the next generation
+	pub elite: usize,
+	/// Individuals competing for every parent slot
+	pub tournament: usize,
+	/// Chance of every child getting mutated
+	pub mutation_rate: f64,
+	/// Rides taken out by the ruin mutation
+	pub ruin_size: usize,
+	pub crossover: Vec<Crossover>,
+	pub mutation: Vec<Mutation>,
+}
+
+impl Default for GaParams {
+	fn default() -> Self {
+		GaParams {
+			initial: DispatchStrategy::BonusAware {
+				bonus_weight: 1.0,
+				horizon: 20000,
+			},
+			population: 20,
+			generations: 50,
+			time_limit: None,
+			elite: 2,
+			tournament: 3,
+			mutation_rate: 0.3,
+			ruin_size: 12,
+			crossover: vec![Crossover::VehicleSwap, Crossover::RouteBased],
+			mutation: vec![Mutation::Relocate, Mutation::Swap, Mutation::Ruin],
+		}
+	}
+}
+
+fn reinsert(routes: &mut Routes, mut rides: Vec<JobId>, rng: &mut Rng) {
+	rng.shuffle(&mut rides);
+	for ride in rides {
+		if routes.vehicle_of(ride).is_some() {
+			continue;
+		}
+		if let Some(ins) = routes.best_insertion(ride) {
+			routes.insert(&ins);
+		}
+	}
+}
+
+/// Puts the donor routes on the given vehicles, the child's own copies of the donated rides
+/// are dropped and whatever the child loses on the way is reinserted where it fits
+fn combine<'a>(
+	base: &Routes<'a>,
+	donor: &Routes<'a>,
+	placement: &[(usize, usize)],
+	rng: &mut Rng,
+) -> Routes<'a> {
+	let mut child = base.clone();
+	let before: HashSet<JobId> = child.assigned().into_iter().collect();
+
+	let donated: HashSet<JobId> = placement
+		.iter()
+		.flat_map(|&(from, _)| donor.route(from).iter().cloned())
+		.collect();
+	let targets: HashSet<usize> = placement.iter().map(|&(_, to)| to).collect();
+
+	for v in 0..child.num_vehicles() {
+		if targets.contains(&v) {
+			child.set_route(v, Vec::new());
+		} else if child.route(v).iter().any(|id| donated.contains(id)) {
+			let kept = child
+				.route(v)
+				.iter()
+				.cloned()
+				.filter(|id| !donated.contains(id))
+				.collect();
+			child.set_route(v, kept);
+		}
+	}
+	for &(from, to) in placement {
+		child.set_route(to, donor.route(from).to_vec());
+	}
+
+	let missing = before
+		.into_iter()
+		.chain(donated)
+		.filter(|&id| child.vehicle_of(id).is_none())
+		.collect();
+	reinsert(&mut child, missing, rng);
+
+	child
+}
+
+fn crossover<'a>(op: Crossover, a: &Routes<'a>, b: &Routes<'a>, rng: &mut Rng) -> Routes<'a> {
+	let num_vehicles = a.num_vehicles();
+	let placement: Vec<(usize, usize)> = match op {
+		Crossover::VehicleSwap => (0..num_vehicles)
+			.filter(|_| rng.chance(0.5))
+			.map(|v| (v, v))
+			.collect(),
+		Crossover::RouteBased => {
+			let mut donors: Vec<usize> = (0..num_vehicles).filter(|&v| !b.route(v).is_empty()).collect();
+			rng.shuffle(&mut donors);
+			donors.truncate(1 + rng.below(usize::max(num_vehicles / 4, 1)));
+
+			let mut taken = HashSet::new();
+			let mut placement = Vec::with_capacity(donors.len());
+			for from in donors {
+				// the vehicle already serving most of these rides, so that little gets shuffled around
+				let mut overlap = vec![0; num_vehicles];
+				for &id in b.route(from) {
+					if let Some(v) = a.vehicle_of(id) {
+						overlap[v] += 1;
+					}
+				}
+				let to = (0..num_vehicles)
+					.filter(|v| !taken.contains(v))
+					.max_by_key(|&v| (overlap[v], v == from));
+				if let Some(to) = to {
+					taken.insert(to);
+					placement.push((from, to));
+				}
+			}
+			placement
+		}
+	};
+
+	combine(a, b, &placement, rng)
+}
+
+fn mutate(routes: &mut Routes, op: Mutation, ruin_size: usize, rng: &mut Rng) {
+	let problem = routes.problem();
+	let assigned = routes.assigned();
+	let ride = match rng.choose(&assigned) {
+		Some(&r) => r,
+		None => return,
+	};
+
+	match op {
+		Mutation::Relocate => {
+			routes.remove(ride);
+			reinsert(routes, vec![ride], rng);
+		}
+		Mutation::Swap => {
+			let other = *rng.choose(&assigned).unwrap();
+			if !routes.swap(ride, other) {
+				return;
+			}
+
+			// a swap that made rides late drops them, give them another chance
+			let lost = [ride, other].iter().cloned().filter(|&id| routes.vehicle_of(id).is_none()).collect();
+			reinsert(routes, lost, rng);
+		}
+		Mutation::Ruin => {
+			let centre = problem.job(ride).start();
+			let mut nearby = assigned;
+			nearby.sort_by_key(|&id| problem.job(id).start().dist(&centre));
+			nearby.truncate(ruin_size);
+
+			let mut unassigned = routes.unassigned();
+			rng.shuffle(&mut unassigned);
+			unassigned.truncate(ruin_size);
+
+			for &id in nearby.iter() {
+				routes.remove(id);
+			}
+			let mut rides = nearby;
+			rides.extend(unassigned);
+			reinsert(routes, rides, rng);
+		}
+	}
+}
+
+/// Index of the tournament winner
+fn select(population: &[Routes], size: usize, rng: &mut Rng) -> usize {
+	(0..usize::max(size, 1))
+		.map(|_| rng.below(population.len()))
+		.max_by_key(|&idx| population[idx].score())
+		.unwrap()
+}
+
+/// Evolves a population grown from the given solution, returns the best one found.
+/// Fails if the solution doesn't fit the problem
+pub fn genetic(
+	problem: &Problem,
+	initial: &Solution,
+	params: &GaParams,
+	rng: &mut Rng,
+) -> Result<Solution, SolutionError> {
+	let started = Instant::now();
+	let seed = Routes::new(problem, initial)?;
+	let size = usize::max(params.population, 2);
+
+	// the rest of the population starts out as heavily mutated copies of the seed
+	let mut population = vec![seed.clone()];
+	while population.len() < size {
+		let mut individual = seed.clone();
+		if !params.mutation.is_empty() {
+			for _ in 0..4 {
+				let op = *rng.choose(&params.mutation).unwrap();
+				mutate(&mut individual, op, params.ruin_size, rng);
+			}
+		}
+		population.push(individual);
+	}
+
+	let mut best = seed;
+	if params.crossover.is_empty() {
+		return Ok(best.to_solution());
+	}
+
+	for _ in 0..params.generations {
+		if params.time_limit.is_some_and(|t| started.elapsed() > t) {
+			break;
+		}
+
+		population.sort_by_key(|r| Reverse(r.score()));
+		if population[0].score() > best.score() {
+			best = population[0].clone();
+		}
+
+		let mut next: Vec<Routes> = population.iter().take(params.elite).cloned().collect();
+		while next.len() < size {
+			let a = select(&population, params.tournament, rng);
+			let b = select(&population, params.tournament, rng);
+			let op = *rng.choose(&params.crossover).unwrap();
+			let mut child = crossover(op, &population[a], &population[b], rng);
+
+			if !params.mutation.is_empty() && rng.chance(params.mutation_rate) {
+				let op = *rng.choose(&params.mutation).unwrap();
+				mutate(&mut child, op, params.ruin_size, rng);
+			}
+			next.push(child);
+		}
+		population = next;
+	}
+
+	for individual in population {
+		if individual.score() > best.score() {
+			best = individual;
+		}
+	}
+
+	Ok(best.to_solution())
+}
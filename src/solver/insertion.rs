@@ -0,0 +1,61 @@
+use scheduler::Problem;
+use solution::{route_score, Solution, SolutionError};
+use solver::routes::Routes;
+use std::fmt::{Display, Error, Formatter};
+
+pub struct InsertionReport {
+	/// Rides that were on a route but couldn't be served on time, these earned nothing
+	pub dropped_late: usize,
+	pub recovered: usize,
+	/// Points earned by the recovered rides
+	pub points: u64,
+	pub score_before: u64,
+	pub score_after: u64,
+}
+
+impl Display for InsertionReport {
+	fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+		write!(
+			f,
+			"Insertion | Recovered rides: {} | Points: {} | Dropped late rides: {} | Score: {} -> {}",
+			self.recovered, self.points, self.dropped_late, self.score_before, self.score_after
+		)
+	}
+}
+
+/// Tries every unassigned ride at every position of every route, longest rides first.
+/// A ride only goes in where none of the rides after it lose points. Fails if the solution doesn't fit the problem
+pub fn insert_unassigned(
+	problem: &Problem,
+	solution: &Solution,
+) -> Result<(Solution, InsertionReport), SolutionError> {
+	solution.validate(problem)?;
+	let score_before = solution.rides().iter().map(|r| route_score(problem, r)).sum();
+	let mut routes = Routes::new(problem, solution)?;
+
+	let assigned_before = solution.rides().iter().map(|r| r.len()).sum::<usize>();
+	let dropped_late = assigned_before - routes.assigned().len();
+
+	let mut candidates = routes.unassigned();
+	candidates.sort_by_key(|&id| -problem.job(id).dist());
+
+	let mut recovered = 0;
+	let mut points = 0;
+	for ride in candidates {
+		if let Some(ins) = routes.best_insertion(ride) {
+			routes.insert(&ins);
+			recovered += 1;
+			points += ins.score;
+		}
+	}
+
+	let report = InsertionReport {
+		dropped_late,
+		recovered,
+		points,
+		score_before,
+		score_after: routes.score(),
+	};
+
+	Ok((routes.to_solution(), report))
+}
@@ -0,0 +1,219 @@
+use rng::Rng;
+use scheduler::{DispatchStrategy, JobId, Problem};
+use solution::{Solution, SolutionError};
+use solver::routes::{Insertion, Routes};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use util::TimeStep;
+
+/// How the rides to remove are picked
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Destroy {
+	/// Uniformly at random
+	Random,
+	/// Rides starting closest to a random seed ride
+	Area,
+	/// Rides with the closest earliest start to a random seed ride
+	TimeWindow,
+	/// Rides with the most time wasted on driving empty or waiting before them
+	WorstSlack,
+}
+
+/// How removed rides are put back
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Repair {
+	/// Cheapest position first, rides in random order
+	BestInsertion,
+	/// Ride with the largest difference between its best and k-th best vehicle first
+	Regret(usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct LnsParams {
+	/// Strategy for the greedy schedule the search starts from
+	pub initial: DispatchStrategy,
+	pub iterations: usize,
+	/// Stop early once this much time has passed
+	pub time_limit: Option<Duration>,
+	/// Rides removed per round
+	pub destroy_size: usize,
+	/// Unassigned rides given a chance at being inserted every round
+	pub unassigned_sample: usize,
+	pub destroy: Vec<Destroy>,
+	pub repair: Vec<Repair>,
+}
+
+impl Default for LnsParams {
+	fn default() -> Self {
+		LnsParams {
+			initial: DispatchStrategy::BonusAware {
+				bonus_weight: 1.0,
+				horizon: 20000,
+			},
+			iterations: 1000,
+			time_limit: None,
+			destroy_size: 12,
+			unassigned_sample: 24,
+			destroy: vec![
+				Destroy::Random,
+				Destroy::Area,
+				Destroy::TimeWindow,
+				Destroy::WorstSlack,
+			],
+			repair: vec![Repair::BestInsertion, Repair::Regret(3)],
+		}
+	}
+}
+
+/// Picks up to `count` assigned rides to remove
+fn select_for_removal(routes: &Routes, op: Destroy, count: usize, rng: &mut Rng) -> Vec<JobId> {
+	let problem = routes.problem();
+	let mut assigned = routes.assigned();
+	if assigned.is_empty() {
+		return assigned;
+	}
+
+	match op {
+		Destroy::Random => {
+			rng.shuffle(&mut assigned);
+		}
+		Destroy::Area => {
+			let seed = problem.job(*rng.choose(&assigned).unwrap()).start();
+			assigned.sort_by_key(|&id| problem.job(id).start().dist(&seed));
+		}
+		Destroy::TimeWindow => {
+			let seed = problem.job(*rng.choose(&assigned).unwrap()).earliest_start();
+			assigned.sort_by_key(|&id| (problem.job(id).earliest_start() - seed).abs());
+		}
+		Destroy::WorstSlack => {
+			let mut waste: HashMap<JobId, TimeStep> = HashMap::with_capacity(assigned.len());
+			for v in 0..routes.num_vehicles() {
+				for t in routes.timings(v) {
+					waste.insert(t.id, t.start - t.departure);
+				}
+			}
+			assigned.sort_by_key(|id| -waste[id]);
+
+			// some noise so that the same rides aren't picked every time
+			let pool = usize::min(assigned.len(), count * 2);
+			rng.shuffle(&mut assigned[..pool]);
+		}
+	}
+
+	assigned.truncate(count);
+	assigned
+}
+
+fn best_insertion_repair(routes: &mut Routes, mut rides: Vec<JobId>, rng: &mut Rng) {
+	rng.shuffle(&mut rides);
+	for ride in rides {
+		if let Some(ins) = routes.best_insertion(ride) {
+			routes.insert(&ins);
+		}
+	}
+}
+
+fn regret_repair(routes: &mut Routes, rides: Vec<JobId>, k: usize) {
+	let k = usize::max(k, 2);
+	let mut options: Vec<(JobId, Vec<Option<Insertion>>)> = rides
+		.into_iter()
+		.map(|ride| {
+			let per_vehicle = (0..routes.num_vehicles())
+				.map(|v| routes.best_insertion_in(ride, v))
+				.collect();
+			(ride, per_vehicle)
+		})
+		.collect();
+
+	while !options.is_empty() {
+		let mut pick: Option<(i64, usize, Insertion)> = None;
+
+		for (idx, (_, per_vehicle)) in options.iter().enumerate() {
+			let mut values: Vec<(i64, Insertion)> = per_vehicle
+				.iter()
+				.filter_map(|o| o.map(|ins| (ins.value(), ins)))
+				.collect();
+			if values.is_empty() {
+				continue;
+			}
+			values.sort_by_key(|&(value, _)| -value);
+
+			// fewer options than k counts as the missing ones being worthless
+			let best = values[0].0;
+			let regret: i64 = (1..k)
+				.map(|i| values.get(i).map_or(best, |&(value, _)| best - value))
+				.sum();
+
+			if pick.is_none_or(|(r, _, _)| regret > r) {
+				pick = Some((regret, idx, values[0].1));
+			}
+		}
+
+		match pick {
+			Some((_, idx, ins)) => {
+				routes.insert(&ins);
+				options.swap_remove(idx);
+
+				// only the vehicle that changed needs another look
+				for (ride, per_vehicle) in options.iter_mut() {
+					per_vehicle[ins.vehicle] = routes.best_insertion_in(*ride, ins.vehicle);
+				}
+			}
+			None => break,
+		}
+	}
+}
+
+/// Destroy and repair search starting from the given solution, returns the best one found.
+/// Fails if the solution doesn't fit the problem
+pub fn lns(
+	problem: &Problem,
+	initial: &Solution,
+	params: &LnsParams,
+	rng: &mut Rng,
+) -> Result<Solution, SolutionError> {
+	let started = Instant::now();
+	let mut current = Routes::new(problem, initial)?;
+	let mut best = current.clone();
+
+	if params.destroy.is_empty() || params.repair.is_empty() {
+		return Ok(best.to_solution());
+	}
+
+	for _ in 0..params.iterations {
+		if params.time_limit.is_some_and(|t| started.elapsed() > t) {
+			break;
+		}
+
+		let mut candidate = current.clone();
+		let destroy = *rng.choose(&params.destroy).unwrap();
+		let repair = *rng.choose(&params.repair).unwrap();
+
+		let removed = select_for_removal(&candidate, destroy, params.destroy_size, rng);
+		let mut unassigned = candidate.unassigned();
+		rng.shuffle(&mut unassigned);
+		unassigned.truncate(params.unassigned_sample);
+
+		for &ride in removed.iter() {
+			candidate.remove(ride);
+		}
+
+		let mut rides = removed;
+		rides.extend(unassigned);
+
+		match repair {
+			Repair::BestInsertion => best_insertion_repair(&mut candidate, rides, rng),
+			Repair::Regret(k) => regret_repair(&mut candidate, rides, k),
+		}
+
+		// sideways moves are fine, they help getting off plateaus
+		if candidate.score() >= current.score() {
+			if candidate.score() > best.score() {
+				best = candidate.clone();
+			}
+			current = candidate;
+		}
+	}
+
+	Ok(best.to_solution())
+}
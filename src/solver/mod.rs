@@ -0,0 +1,68 @@
+pub mod exact;
+pub mod genetic;
+pub mod insertion;
+pub mod lns;
+pub mod routes;
+pub mod sequence;
+pub mod tabu;
+
+use error::Error;
+use rng::Rng;
+use scheduler::{DispatchStrategy, JobScheduler, Problem, RepositionPolicy};
+use solution::Solution;
+
+pub use self::exact::{exact, ExactError, ExactParams};
+pub use self::genetic::{Crossover, GaParams, Mutation};
+pub use self::insertion::{insert_unassigned, InsertionReport};
+pub use self::lns::{Destroy, LnsParams, Repair};
+pub use self::sequence::sequence;
+pub use self::tabu::TabuParams;
+
+/// Every way of producing a solution, for use with `solve`
+#[derive(Clone, Debug)]
+pub enum Solver {
+	/// The tick-by-tick simulation
+	Greedy(DispatchStrategy, RepositionPolicy),
+	/// Large neighbourhood search on top of the greedy schedule
+	Lns(LnsParams),
+	/// Genetic algorithm seeded with the greedy schedule
+	Genetic(GaParams),
+	/// Tabu search on top of the greedy schedule
+	Tabu(TabuParams),
+}
+
+/// Greedy schedule, with ties broken by the generator
+pub fn greedy(
+	problem: &Problem,
+	strategy: DispatchStrategy,
+	reposition: RepositionPolicy,
+	rng: &mut Rng,
+) -> Result<Solution, Error> {
+	let mut runner = JobScheduler::from_problem(problem);
+	runner.set_dispatch_strategy(strategy);
+	runner.set_reposition_policy(reposition);
+	runner.set_rng(rng.fork());
+	runner.clear_observers();
+	runner.run()?;
+
+	Ok(runner.solution())
+}
+
+/// Fails only if the greedy schedule, which every solver starts from, does or turns out invalid
+pub fn solve(problem: &Problem, solver: &Solver, rng: &mut Rng) -> Result<Solution, Error> {
+	Ok(match *solver {
+		Solver::Greedy(strategy, reposition) => greedy(problem, strategy, reposition, rng)?,
+		Solver::Lns(ref params) => {
+			let initial = greedy(problem, params.initial, RepositionPolicy::Stay, rng)?;
+			lns::lns(problem, &initial, params, rng)?
+		}
+		Solver::Genetic(ref params) => {
+			let initial = greedy(problem, params.initial, RepositionPolicy::Stay, rng)?;
+			genetic::genetic(problem, &initial, params, rng)?
+		}
+		Solver::Tabu(ref params) => {
+			let initial = greedy(problem, params.initial, RepositionPolicy::Stay, rng)?;
+			tabu::tabu(problem, &initial, params, rng)?
+		}
+	})
+}
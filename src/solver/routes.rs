@@ -0,0 +1,286 @@
+use scheduler::{JobId, Problem};
+use solution::{route_timings, RideTiming, Solution, SolutionError};
+use util::{Coord, TimeStep};
+
+/// Where and how a ride would fit into a vehicle's route
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Insertion {
+	pub ride: JobId,
+	pub vehicle: usize,
+	/// Index in the route the ride would take
+	pub pos: usize,
+	/// Points the ride itself earns there
+	pub score: u64,
+	/// Steps the following ride is pushed back by, or the steps used up when appended at the end
+	pub cost: TimeStep,
+}
+
+impl Insertion {
+	/// Single figure of merit, points first, then the time it costs
+	pub fn value(&self) -> i64 {
+		self.score as i64 * 1_000_000 - self.cost as i64
+	}
+}
+
+/// Working copy of a solution for the improvement heuristics.
+/// Only rides that are served on time are kept on the routes, the rest are unassigned
+#[derive(Clone)]
+pub struct Routes<'a> {
+	problem: &'a Problem,
+	routes: Vec<Vec<JobId>>,
+	timings: Vec<Vec<RideTiming>>,
+	/// How much later a vehicle can arrive at each ride without it or any ride after it losing points
+	max_delay: Vec<Vec<TimeStep>>,
+	vehicle_of: Vec<Option<usize>>,
+	score: u64,
+}
+
+impl<'a> Routes<'a> {
+	pub fn empty(problem: &'a Problem) -> Routes<'a> {
+		let num_vehicles = problem.num_vehicles() as usize;
+		Routes {
+			problem,
+			routes: vec![Vec::new(); num_vehicles],
+			timings: vec![Vec::new(); num_vehicles],
+			max_delay: vec![Vec::new(); num_vehicles],
+			vehicle_of: vec![None; problem.num_jobs() as usize],
+			score: 0,
+		}
+	}
+
+	/// Late rides are dropped, which never costs the remaining rides any points. Fails if the
+	/// solution doesn't fit the problem
+	pub fn new(problem: &'a Problem, solution: &Solution) -> Result<Routes<'a>, SolutionError> {
+		solution.validate(problem)?;
+		let mut out = Routes::empty(problem);
+
+		for (v, rides) in solution.rides().iter().enumerate() {
+			out.routes[v] = route_timings(problem, rides)
+				.iter()
+				.filter(|t| t.on_time())
+				.map(|t| t.id)
+				.collect();
+			for &id in out.routes[v].iter() {
+				out.vehicle_of[id as usize] = Some(v);
+			}
+			out.refresh(v);
+		}
+
+		Ok(out)
+	}
+
+	pub fn problem(&self) -> &'a Problem {
+		self.problem
+	}
+
+	pub fn score(&self) -> u64 {
+		self.score
+	}
+
+	pub fn num_vehicles(&self) -> usize {
+		self.routes.len()
+	}
+
+	pub fn route(&self, vehicle: usize) -> &[JobId] {
+		&self.routes[vehicle]
+	}
+
+	pub fn timings(&self, vehicle: usize) -> &[RideTiming] {
+		&self.timings[vehicle]
+	}
+
+	pub fn vehicle_of(&self, ride: JobId) -> Option<usize> {
+		self.vehicle_of[ride as usize]
+	}
+
+	pub fn assigned(&self) -> Vec<JobId> {
+		self.routes.iter().flat_map(|r| r.iter().cloned()).collect()
+	}
+
+	pub fn unassigned(&self) -> Vec<JobId> {
+		self.vehicle_of
+			.iter()
+			.enumerate()
+			.filter(|&(_, v)| v.is_none())
+			.map(|(id, _)| id as JobId)
+			.collect()
+	}
+
+	pub fn to_solution(&self) -> Solution {
+		Solution::new(self.routes.clone())
+	}
+
+	fn refresh(&mut self, vehicle: usize) {
+		let old: u64 = self.timings[vehicle].iter().map(|t| t.score).sum();
+		self.timings[vehicle] = route_timings(self.problem, &self.routes[vehicle]);
+		let new: u64 = self.timings[vehicle].iter().map(|t| t.score).sum();
+
+		self.score = self.score - old + new;
+
+		// waiting soaks up delays, a ride that earned the bonus can't start any later
+		let problem = self.problem;
+		let timings = &self.timings[vehicle];
+		let mut max_delay = vec![0; timings.len()];
+		let mut next = TimeStep::MAX;
+		for (idx, t) in timings.iter().enumerate().rev() {
+			let job = problem.job(t.id);
+			let headroom = if t.score == 0 {
+				TimeStep::MAX
+			} else if t.start == job.earliest_start() && problem.ride_bonus() > 0 {
+				0
+			} else {
+				i32::min(job.latest_finish(), problem.max_tsteps()) - t.finish
+			};
+
+			next = (t.start - t.arrival).saturating_add(i32::min(headroom, next));
+			max_delay[idx] = next;
+		}
+		self.max_delay[vehicle] = max_delay;
+	}
+
+	/// Replaces a vehicle's route wholesale, rides that end up late are dropped
+	pub fn set_route(&mut self, vehicle: usize, rides: Vec<JobId>) {
+		for &id in self.routes[vehicle].iter() {
+			self.vehicle_of[id as usize] = None;
+		}
+
+		self.routes[vehicle] = route_timings(self.problem, &rides)
+			.iter()
+			.filter(|t| t.on_time())
+			.map(|t| t.id)
+			.collect();
+		for &id in self.routes[vehicle].iter() {
+			assert!(self.vehicle_of[id as usize].is_none());
+			self.vehicle_of[id as usize] = Some(vehicle);
+		}
+		self.refresh(vehicle);
+	}
+
+	/// Returns false if the ride wasn't assigned to begin with
+	pub fn remove(&mut self, ride: JobId) -> bool {
+		match self.vehicle_of[ride as usize].take() {
+			Some(v) => {
+				self.routes[v].retain(|&id| id != ride);
+				self.refresh(v);
+				true
+			}
+			None => false,
+		}
+	}
+
+	/// Two rides on different vehicles trade places, rides that end up late are dropped.
+	/// Returns false if either ride isn't assigned or both are on the same vehicle
+	pub fn swap(&mut self, a: JobId, b: JobId) -> bool {
+		let (va, vb) = match (self.vehicle_of(a), self.vehicle_of(b)) {
+			(Some(va), Some(vb)) if va != vb => (va, vb),
+			_ => return false,
+		};
+
+		let swapped = |route: &[JobId]| -> Vec<JobId> {
+			route
+				.iter()
+				.map(|&id| if id == a { b } else if id == b { a } else { id })
+				.collect()
+		};
+		let (ra, rb) = (swapped(&self.routes[va]), swapped(&self.routes[vb]));
+
+		// free up the ride headed for the first vehicle before placing it there
+		let without_a = rb.iter().cloned().filter(|&id| id != a).collect();
+		self.set_route(vb, without_a);
+		self.set_route(va, ra);
+		self.set_route(vb, rb);
+
+		true
+	}
+
+	/// Evaluates the ride at the given position, None if it would be late
+	/// or if any ride after it would lose points
+	pub fn insertion_at(&self, ride: JobId, vehicle: usize, pos: usize) -> Option<Insertion> {
+		let problem = self.problem;
+		let job = problem.job(ride);
+		let route = &self.routes[vehicle];
+		let timings = &self.timings[vehicle];
+
+		let (cur_pos, prev_finish) = if pos == 0 {
+			(Coord::default(), 0)
+		} else {
+			(problem.job(route[pos - 1]).end(), timings[pos - 1].finish)
+		};
+
+		let start = i32::max(prev_finish + cur_pos.dist(&job.start()), job.earliest_start());
+		let finish = start + job.dist();
+		if finish > job.latest_finish() || finish > problem.max_tsteps() {
+			return None;
+		}
+
+		let score = job.dist() as u64
+			+ if start == job.earliest_start() {
+				problem.ride_bonus() as u64
+			} else {
+				0
+			};
+		let cost = match route.get(pos) {
+			Some(&next_id) => {
+				let next = &timings[pos];
+				let delay = finish + job.end().dist(&problem.job(next_id).start()) - next.arrival;
+				if delay > self.max_delay[vehicle][pos] {
+					return None;
+				}
+
+				i32::max(delay - (next.start - next.arrival), 0)
+			}
+			None => finish - prev_finish,
+		};
+
+		Some(Insertion {
+			ride,
+			vehicle,
+			pos,
+			score,
+			cost,
+		})
+	}
+
+	/// Best position for the ride in the given vehicle's route
+	pub fn best_insertion_in(&self, ride: JobId, vehicle: usize) -> Option<Insertion> {
+		let job = self.problem.job(ride);
+		let timings = &self.timings[vehicle];
+		let mut best: Option<Insertion> = None;
+
+		for pos in 0..=timings.len() {
+			// finish times only grow along the route
+			if pos > 0 && timings[pos - 1].finish + job.dist() > job.latest_finish() {
+				break;
+			}
+
+			if let Some(ins) = self.insertion_at(ride, vehicle, pos) {
+				if best.is_none_or(|b| ins.value() > b.value()) {
+					best = Some(ins);
+				}
+			}
+		}
+
+		best
+	}
+
+	/// Best position in every vehicle that can take the ride
+	pub fn insertions(&self, ride: JobId) -> Vec<Insertion> {
+		(0..self.num_vehicles())
+			.filter_map(|v| self.best_insertion_in(ride, v))
+			.collect()
+	}
+
+	pub fn best_insertion(&self, ride: JobId) -> Option<Insertion> {
+		self.insertions(ride)
+			.into_iter()
+			.max_by_key(|ins| ins.value())
+	}
+
+	pub fn insert(&mut self, ins: &Insertion) {
+		assert!(self.vehicle_of[ins.ride as usize].is_none());
+
+		self.routes[ins.vehicle].insert(ins.pos, ins.ride);
+		self.vehicle_of[ins.ride as usize] = Some(ins.vehicle);
+		self.refresh(ins.vehicle);
+	}
+}
@@ -0,0 +1,160 @@
+use scheduler::{JobId, Problem};
+use solution::{route_score, route_timings};
+use solver::routes::Routes;
+use util::{Coord, TimeStep};
+
+/// Ride sets up to this size are ordered exactly, larger ones heuristically
+pub const EXACT_RIDES: usize = 12;
+
+/// Partial route ending in a given ride, kept only while no other one with the same rides
+/// scores at least as much and is free at least as early
+struct Label {
+	score: u64,
+	finish: TimeStep,
+	ride: usize,
+	parent: Option<usize>,
+}
+
+/// Bitmask dynamic programme over (rides served, last ride) keeping the pareto front of
+/// (score, finish time) in every state
+fn exact_order(problem: &Problem, rides: &[JobId]) -> Vec<JobId> {
+	let n = rides.len();
+	let mut labels: Vec<Label> = Vec::new();
+	let mut states: Vec<Vec<Vec<usize>>> = vec![vec![Vec::new(); n]; 1 << n];
+	let mut best: Option<usize> = None;
+
+	let extend = |from: (Coord, TimeStep), ride: JobId| -> Option<(u64, TimeStep)> {
+		let job = problem.job(ride);
+		let start = TimeStep::max(from.1 + from.0.dist(&job.start()), job.earliest_start());
+		let finish = start + job.dist();
+		if finish > job.latest_finish() || finish > problem.max_tsteps() {
+			return None;
+		}
+
+		let bonus = if start == job.earliest_start() {
+			problem.ride_bonus() as u64
+		} else {
+			0
+		};
+		Some((job.dist() as u64 + bonus, finish))
+	};
+
+	for mask in 0..(1usize << n) {
+		for j in 0..n {
+			if mask & (1 << j) != 0 {
+				continue;
+			}
+
+			// the empty route, or every label ending in a ride of this set
+			let mut origins: Vec<(Option<usize>, Coord, TimeStep, u64)> = Vec::new();
+			if mask == 0 {
+				origins.push((None, Coord::default(), 0, 0));
+			}
+			for last in 0..n {
+				for &idx in states[mask][last].iter() {
+					let label = &labels[idx];
+					origins.push((Some(idx), problem.job(rides[last]).end(), label.finish, label.score));
+				}
+			}
+
+			for (parent, pos, free_at, score) in origins {
+				let (points, finish) = match extend((pos, free_at), rides[j]) {
+					Some(e) => e,
+					None => continue,
+				};
+				let score = score + points;
+
+				let front = &mut states[mask | (1 << j)][j];
+				if front
+					.iter()
+					.any(|&idx| labels[idx].score >= score && labels[idx].finish <= finish)
+				{
+					continue;
+				}
+				front.retain(|&idx| !(labels[idx].score <= score && labels[idx].finish >= finish));
+
+				labels.push(Label {
+					score,
+					finish,
+					ride: j,
+					parent,
+				});
+				let idx = labels.len() - 1;
+				front.push(idx);
+				if best.is_none_or(|b| score > labels[b].score) {
+					best = Some(idx);
+				}
+			}
+		}
+	}
+
+	let mut order = Vec::new();
+	let mut cur = best;
+	while let Some(idx) = cur {
+		order.push(rides[labels[idx].ride]);
+		cur = labels[idx].parent;
+	}
+	order.reverse();
+
+	order
+}
+
+/// Cheapest insertion by earliest start, then every ride gets moved to its best position
+/// for as long as that keeps paying off
+fn heuristic_order(problem: &Problem, rides: &[JobId]) -> Vec<JobId> {
+	let mut sorted = rides.to_vec();
+	sorted.sort_by_key(|&id| problem.job(id).earliest_start());
+
+	let mut routes = Routes::empty(problem);
+	for &ride in sorted.iter() {
+		if let Some(ins) = routes.best_insertion_in(ride, 0) {
+			routes.insert(&ins);
+		}
+	}
+
+	let mut improved = true;
+	while improved {
+		improved = false;
+		for &ride in sorted.iter() {
+			let before = routes.score();
+			let route = routes.route(0).to_vec();
+
+			routes.remove(ride);
+			for &other in sorted.iter() {
+				if routes.vehicle_of(other).is_none() {
+					if let Some(ins) = routes.best_insertion_in(other, 0) {
+						routes.insert(&ins);
+					}
+				}
+			}
+
+			if routes.score() > before {
+				improved = true;
+			} else {
+				routes.set_route(0, route);
+			}
+		}
+	}
+
+	routes.route(0).to_vec()
+}
+
+/// Best order, or subset, for a single vehicle to serve the given rides in.
+/// Rides that can't be served on time are left out of the result, and it never
+/// scores less than the rides in the order they were given
+pub fn sequence(problem: &Problem, rides: &[JobId]) -> Vec<JobId> {
+	if rides.len() <= EXACT_RIDES {
+		return exact_order(problem, rides);
+	}
+
+	let order = heuristic_order(problem, rides);
+	if route_score(problem, &order) >= route_score(problem, rides) {
+		order
+	} else {
+		route_timings(problem, rides)
+			.iter()
+			.filter(|t| t.on_time())
+			.map(|t| t.id)
+			.collect()
+	}
+}
@@ -0,0 +1,221 @@
+use rng::Rng;
+use scheduler::{DispatchStrategy, JobId, Problem};
+use solution::{route_score, Solution, SolutionError};
+use solver::routes::{Insertion, Routes};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug)]
+pub struct TabuParams {
+	/// Strategy for the greedy schedule the search starts from
+	pub initial: DispatchStrategy,
+	pub iterations: usize,
+	/// Stop early once this much time has passed
+	pub time_limit: Option<Duration>,
+	/// Iterations a ride is kept from returning to a vehicle it left
+	pub tenure: usize,
+	/// Moves sampled every iteration, the best allowed one is taken
+	pub candidates: usize,
+	/// Chance of a move bringing in an unassigned ride rather than shuffling assigned ones
+	pub unassigned_share: f64,
+}
+
+impl Default for TabuParams {
+	fn default() -> Self {
+		TabuParams {
+			initial: DispatchStrategy::BonusAware {
+				bonus_weight: 1.0,
+				horizon: 20000,
+			},
+			iterations: 5000,
+			time_limit: None,
+			tenure: 30,
+			candidates: 40,
+			unassigned_share: 0.3,
+		}
+	}
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Move {
+	/// Moves the ride off its vehicle, if it has one, into the given position
+	Relocate(Insertion),
+	Swap(JobId, JobId),
+	/// An unassigned ride takes the place of an assigned one, which becomes unassigned
+	Exchange(JobId, JobId),
+}
+
+/// Score of the vehicle's route without the ride
+fn score_without(routes: &Routes, vehicle: usize, ride: JobId) -> u64 {
+	let route: Vec<JobId> = routes.route(vehicle).iter().cloned().filter(|&id| id != ride).collect();
+	route_score(routes.problem(), &route)
+}
+
+fn route_points(routes: &Routes, vehicle: usize) -> u64 {
+	routes.timings(vehicle).iter().map(|t| t.score).sum()
+}
+
+fn replaced(routes: &Routes, vehicle: usize, a: JobId, b: JobId) -> Vec<JobId> {
+	routes
+		.route(vehicle)
+		.iter()
+		.map(|&id| if id == a { b } else if id == b { a } else { id })
+		.collect()
+}
+
+/// A random move and the change in score it would bring
+fn sample_move(
+	routes: &Routes,
+	assigned: &[JobId],
+	unassigned: &[JobId],
+	params: &TabuParams,
+	rng: &mut Rng,
+) -> Option<(Move, i64)> {
+	let problem = routes.problem();
+	let num_vehicles = routes.num_vehicles();
+
+	if assigned.is_empty() || rng.chance(params.unassigned_share) {
+		let &ride = rng.choose(unassigned)?;
+		let vehicle = rng.below(num_vehicles);
+		if let Some(ins) = routes.best_insertion_in(ride, vehicle) {
+			return Some((Move::Relocate(ins), ins.score as i64));
+		}
+
+		// no room, push out whichever ride is served closest to when this one wants to start
+		let timings = routes.timings(vehicle);
+		let earliest_start = problem.job(ride).earliest_start();
+		let out = timings.iter().min_by_key(|t| (t.start - earliest_start).abs())?.id;
+		let after = route_score(problem, &replaced(routes, vehicle, out, ride));
+		return Some((Move::Exchange(ride, out), after as i64 - route_points(routes, vehicle) as i64));
+	}
+
+	let &ride = rng.choose(assigned)?;
+	let from = routes.vehicle_of(ride).unwrap();
+
+	if rng.chance(0.5) {
+		let to = rng.below(num_vehicles);
+		if to == from {
+			return None;
+		}
+
+		let ins = routes.best_insertion_in(ride, to)?;
+		let removal = score_without(routes, from, ride) as i64 - route_points(routes, from) as i64;
+		Some((Move::Relocate(ins), removal + ins.score as i64))
+	} else {
+		let &other = rng.choose(assigned)?;
+		let to = routes.vehicle_of(other).unwrap();
+		if to == from {
+			return None;
+		}
+
+		let before = route_points(routes, from) + route_points(routes, to);
+		let after = route_score(problem, &replaced(routes, from, ride, other))
+			+ route_score(problem, &replaced(routes, to, ride, other));
+		Some((Move::Swap(ride, other), after as i64 - before as i64))
+	}
+}
+
+/// The (ride, vehicle) pairs the move would create
+fn arrivals(routes: &Routes, mv: &Move) -> Vec<(JobId, usize)> {
+	match *mv {
+		Move::Relocate(ins) => vec![(ins.ride, ins.vehicle)],
+		Move::Swap(a, b) => vec![
+			(a, routes.vehicle_of(b).unwrap()),
+			(b, routes.vehicle_of(a).unwrap()),
+		],
+		Move::Exchange(ride, out) => vec![(ride, routes.vehicle_of(out).unwrap())],
+	}
+}
+
+/// The (ride, vehicle) pairs the move would break up
+fn departures(routes: &Routes, mv: &Move) -> Vec<(JobId, usize)> {
+	match *mv {
+		Move::Relocate(ins) => routes
+			.vehicle_of(ins.ride)
+			.map(|v| (ins.ride, v))
+			.into_iter()
+			.collect(),
+		Move::Swap(a, b) => vec![
+			(a, routes.vehicle_of(a).unwrap()),
+			(b, routes.vehicle_of(b).unwrap()),
+		],
+		Move::Exchange(_, out) => vec![(out, routes.vehicle_of(out).unwrap())],
+	}
+}
+
+fn apply(routes: &mut Routes, mv: &Move) {
+	match *mv {
+		Move::Relocate(ins) => {
+			routes.remove(ins.ride);
+			routes.insert(&ins);
+		}
+		Move::Swap(a, b) => {
+			routes.swap(a, b);
+		}
+		Move::Exchange(ride, out) => {
+			let vehicle = routes.vehicle_of(out).unwrap();
+			let route = replaced(routes, vehicle, out, ride);
+			routes.set_route(vehicle, route);
+		}
+	}
+}
+
+/// Tabu search over relocations, swaps and exchanges with unassigned rides starting from the given solution,
+/// returns the best one found. Worsening moves are taken when nothing better is allowed.
+/// Fails if the solution doesn't fit the problem
+pub fn tabu(
+	problem: &Problem,
+	initial: &Solution,
+	params: &TabuParams,
+	rng: &mut Rng,
+) -> Result<Solution, SolutionError> {
+	let started = Instant::now();
+	let mut current = Routes::new(problem, initial)?;
+	let mut best = current.clone();
+	// (ride, vehicle) -> iteration the pair stops being tabu
+	let mut tabu_until: HashMap<(JobId, usize), usize> = HashMap::new();
+
+	for iteration in 0..params.iterations {
+		if params.time_limit.is_some_and(|t| started.elapsed() > t) {
+			break;
+		}
+
+		let assigned = current.assigned();
+		let unassigned = current.unassigned();
+
+		let mut pick: Option<(Move, i64)> = None;
+		for _ in 0..params.candidates {
+			let (mv, delta) = match sample_move(&current, &assigned, &unassigned, params, rng) {
+				Some(m) => m,
+				None => continue,
+			};
+
+			let is_tabu = arrivals(&current, &mv)
+				.iter()
+				.any(|pair| tabu_until.get(pair).is_some_and(|&until| until > iteration));
+			let aspires = current.score() as i64 + delta > best.score() as i64;
+			if is_tabu && !aspires {
+				continue;
+			}
+
+			if pick.is_none_or(|(_, d)| delta > d) {
+				pick = Some((mv, delta));
+			}
+		}
+
+		let mv = match pick {
+			Some((mv, _)) => mv,
+			None => continue,
+		};
+		for pair in departures(&current, &mv) {
+			tabu_until.insert(pair, iteration + 1 + params.tenure);
+		}
+		apply(&mut current, &mv);
+
+		if current.score() > best.score() {
+			best = current.clone();
+		}
+	}
+
+	Ok(best.to_solution())
+}
@@ -1,7 +1,12 @@
 use std;
 use std::cmp::Ordering;
-use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read, Write};
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
 fn manhattan_dist(a: &Coord, b: &Coord) -> i32 {
     i32::abs(a.x - b.x) + i32::abs(a.y - b.y)
@@ -17,7 +22,7 @@ pub fn cmp_i32(a: i32, b: i32) -> Ordering {
     }
 }
 
-#[derive(Copy, Clone, Eq, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Debug)]
 pub struct Coord {
     pub x: i32,
     pub y: i32,
@@ -35,17 +40,18 @@ impl Coord {
     pub fn is_origin(&self) -> bool {
         self == &Coord::default()
     }
-}
 
-impl Default for Coord {
-    fn default() -> Self {
-        Coord { x: 0, y: 0 }
-    }
-}
+    /// Intersection reached after driving the given number of steps towards `to`, going along
+    /// x first and then along y. Stops at `to`
+    pub fn towards(&self, to: &Coord, steps: i32) -> Coord {
+        let steps = i32::max(steps, 0);
+        let dx = i32::min((to.x - self.x).abs(), steps);
+        let dy = i32::min((to.y - self.y).abs(), steps - dx);
 
-impl PartialEq for Coord {
-    fn eq(&self, other: &Coord) -> bool {
-        self.x == other.x && self.y == other.y
+        Coord {
+            x: self.x + dx * (to.x - self.x).signum(),
+            y: self.y + dy * (to.y - self.y).signum(),
+        }
     }
 }
 
@@ -56,23 +62,79 @@ pub enum FileIOError {
     CouldntOpenFile(std::io::Error),
     LineReadError(std::io::Error),
     LineWriteError(std::io::Error),
+    /// Flushing buffered output, or syncing it to disk
+    FlushError(std::io::Error),
+    /// Moving a finished file into place
+    RenameError(std::io::Error),
 }
 
+impl fmt::Display for FileIOError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FileIOError::CouldntOpenFile(ref e) => write!(f, "couldn't open file: {}", e),
+            FileIOError::LineReadError(ref e) => write!(f, "couldn't read line: {}", e),
+            FileIOError::LineWriteError(ref e) => write!(f, "couldn't write line: {}", e),
+            FileIOError::FlushError(ref e) => write!(f, "couldn't flush output: {}", e),
+            FileIOError::RenameError(ref e) => write!(f, "couldn't move file into place: {}", e),
+        }
+    }
+}
+
+impl error::Error for FileIOError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            FileIOError::CouldntOpenFile(ref e)
+            | FileIOError::LineReadError(ref e)
+            | FileIOError::LineWriteError(ref e)
+            | FileIOError::FlushError(ref e)
+            | FileIOError::RenameError(ref e) => Some(e),
+        }
+    }
+}
+
+/// Path that stands for stdin when reading and stdout when writing
+pub const STDIO_PATH: &str = "-";
+
+/// Reads lines from a file, stdin or anything else implementing `Read`
 pub struct FileReader {
-    reader: Box<BufReader<File>>,
+    reader: Box<dyn BufRead>,
 }
 
 impl FileReader {
     pub fn new(path: &str) -> Result<FileReader, FileIOError> {
         let out = FileReader {
-            reader: Box::new(BufReader::new(try!(
-                File::open(path).map_err(|e| FileIOError::CouldntOpenFile(e))
-            ))),
+            reader: Box::new(BufReader::new(
+                File::open(path).map_err(FileIOError::CouldntOpenFile)?,
+            )),
         };
 
         Ok(out)
     }
 
+    /// Like `new`, but `-` reads stdin
+    pub fn open(path: &str) -> Result<FileReader, FileIOError> {
+        if path == STDIO_PATH {
+            Ok(FileReader::stdin())
+        } else {
+            FileReader::new(path)
+        }
+    }
+
+    pub fn stdin() -> FileReader {
+        FileReader::from_reader(io::stdin())
+    }
+
+    pub fn from_reader<R: Read + 'static>(reader: R) -> FileReader {
+        FileReader {
+            reader: Box::new(BufReader::new(reader)),
+        }
+    }
+
+    /// Reads the given text, handy for problems built in memory
+    pub fn from_string(contents: &str) -> FileReader {
+        FileReader::from_reader(Cursor::new(contents.as_bytes().to_vec()))
+    }
+
     pub fn read_all_lines(self) -> Result<Vec<String>, Vec<FileIOError>> {
         let mut lines = Vec::<String>::new();
         let mut errs = Vec::<FileIOError>::new();
@@ -90,30 +152,110 @@ impl FileReader {
     }
 }
 
+/// Buffered writer for a file, stdout or anything else implementing `Write`
 pub struct FileWriter {
-    writer: Box<BufWriter<File>>,
+    writer: BufWriter<Box<dyn Write>>,
+    /// Handle to sync to disk on `finish`, when writing to a file
+    file: Option<File>,
 }
 
 impl FileWriter {
     pub fn new(path: &str) -> Result<FileWriter, FileIOError> {
-        let out = FileWriter {
-            writer: Box::new(BufWriter::new(try!(
-                File::create(path).map_err(|e| FileIOError::CouldntOpenFile(e))
-            ))),
-        };
+        let file = File::create(path).map_err(FileIOError::CouldntOpenFile)?;
+        let handle = file.try_clone().map_err(FileIOError::CouldntOpenFile)?;
 
-        Ok(out)
+        Ok(FileWriter {
+            writer: BufWriter::new(Box::new(file)),
+            file: Some(handle),
+        })
+    }
+
+    /// Like `new`, but `-` writes to stdout
+    pub fn create(path: &str) -> Result<FileWriter, FileIOError> {
+        if path == STDIO_PATH {
+            Ok(FileWriter::stdout())
+        } else {
+            FileWriter::new(path)
+        }
+    }
+
+    pub fn stdout() -> FileWriter {
+        FileWriter::from_writer(io::stdout())
+    }
+
+    pub fn from_writer<W: Write + 'static>(writer: W) -> FileWriter {
+        FileWriter {
+            writer: BufWriter::new(Box::new(writer)),
+            file: None,
+        }
     }
 
     pub fn write_line(&mut self, line: &str) -> Result<(), FileIOError> {
         self.writer
             .write_all(line.as_bytes())
-            .map_err(|e| FileIOError::LineWriteError(e))
+            .map_err(FileIOError::LineWriteError)
+    }
+
+    /// Flushes everything written so far, and syncs it to disk when writing to a file
+    pub fn finish(mut self) -> Result<(), FileIOError> {
+        self.writer.flush().map_err(FileIOError::FlushError)?;
+        match self.file {
+            Some(ref file) => file.sync_all().map_err(FileIOError::FlushError),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Write for FileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
     }
 }
 
 impl Drop for FileWriter {
     fn drop(&mut self) {
-        self.writer.flush().expect("Couldn't flush file");
+        // best effort, call finish to find out whether it worked
+        let _ = self.writer.flush();
+    }
+}
+
+/// Numbers the temporary files of `write_atomically` within the process
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Replaces the file at `path` with `contents` all at once. They're written to a temporary file
+/// next to it first, synced to disk and then renamed over the original, so that a crash midway
+/// leaves either the old file or the new one but never half of one. The temporary file is named
+/// after the process and a counter, so concurrent writers never share one
+pub fn write_atomically(path: &str, contents: &str) -> Result<(), FileIOError> {
+    let temp = format!(
+        "{}.{}.{}.tmp",
+        path,
+        process::id(),
+        TEMP_FILES.fetch_add(1, AtomicOrdering::Relaxed)
+    );
+    let written = FileWriter::new(&temp).and_then(|mut writer| {
+        writer.write_line(contents)?;
+        writer.finish()
+    });
+
+    let renamed = written.and_then(|_| fs::rename(&temp, path).map_err(FileIOError::RenameError));
+    if renamed.is_err() {
+        let _ = fs::remove_file(&temp);
+        return renamed;
     }
+
+    // make the rename itself durable, where the platform allows syncing directories
+    let dir = match Path::new(path).parent() {
+        Some(d) if !d.as_os_str().is_empty() => d,
+        _ => Path::new("."),
+    };
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+
+    Ok(())
 }
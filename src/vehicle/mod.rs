@@ -1,7 +1,8 @@
 extern crate itertools;
 
+use error::Error;
 use scheduler::{Job, JobId};
-use scheduler::TickComplete;
+use scheduler::{Segment, SegmentKind, TickComplete};
 use std::hash::{Hash, Hasher};
 use util::{Coord, TimeStep};
 
@@ -11,11 +12,19 @@ enum RideTaskType {
     DrivingToStart,
     WaitingAtStart,
     DrivingToEnd,
+    Repositioning,
 }
 
 struct RideTask {
     task_type: RideTaskType,
     rem_steps: TimeStep,
+    steps: TimeStep,
+    /// Where the task takes the vehicle, from and to the same place when waiting
+    from: Coord,
+    to: Coord,
+    job: Option<JobId>,
+    /// Step in which the task first made progress
+    begin: Option<TimeStep>,
 }
 
 impl Eq for RideTask {}
@@ -30,30 +39,66 @@ impl Hash for RideTask {
         // weak hash, but we don't care as the task object is meant to be transient
         state.write_u32(self.task_type as u32);
         state.write_i32(self.rem_steps);
-        state.finish();
     }
 }
 
 impl RideTask {
-    fn new(task_type: RideTaskType, rem_steps: TimeStep) -> RideTask {
+    fn new(
+        task_type: RideTaskType,
+        rem_steps: TimeStep,
+        from: Coord,
+        to: Coord,
+        job: Option<JobId>,
+    ) -> RideTask {
         RideTask {
             task_type,
             rem_steps,
+            steps: rem_steps,
+            from,
+            to,
+            job,
+            begin: None,
         }
     }
 
-    fn step(&mut self) -> bool {
-        assert!(self.task_type != RideTaskType::Invalid);
-        assert!(self.is_idle() == false);
+    /// Whether the task is done after the step, or why it can't be worked on
+    fn step(&mut self, current_step: TimeStep) -> Result<bool, &'static str> {
+        if self.task_type == RideTaskType::Invalid {
+            return Err("stepped an invalid task");
+        } else if self.is_idle() {
+            return Err("stepped a task that is already done");
+        }
 
+        self.begin.get_or_insert(current_step);
         self.rem_steps -= 1;
-        self.is_idle()
+        Ok(self.is_idle())
+    }
+
+    fn segment(&self) -> Option<Segment> {
+        let kind = match self.task_type {
+            RideTaskType::DrivingToStart => SegmentKind::Deadhead,
+            RideTaskType::WaitingAtStart => SegmentKind::Waiting,
+            RideTaskType::DrivingToEnd => SegmentKind::OnRide,
+            RideTaskType::Repositioning => SegmentKind::Repositioning,
+            RideTaskType::Invalid => return None,
+        };
+
+        self.begin.map(|from| Segment {
+            kind,
+            job: self.job,
+            from,
+            to: from + self.steps - self.rem_steps,
+        })
     }
 
     fn is_idle(&self) -> bool {
         self.rem_steps == 0
     }
 
+    fn position(&self) -> Coord {
+        self.from.towards(&self.to, self.steps - self.rem_steps)
+    }
+
     fn has_arrived_at_start(&self) -> bool {
         self.task_type == RideTaskType::DrivingToStart && self.is_idle()
     }
@@ -66,6 +111,10 @@ impl RideTask {
         self.task_type == RideTaskType::DrivingToEnd && self.is_idle()
     }
 
+    fn has_repositioned(&self) -> bool {
+        self.task_type == RideTaskType::Repositioning && self.is_idle()
+    }
+
     fn task_type(&self) -> RideTaskType {
         self.task_type
     }
@@ -79,6 +128,7 @@ pub struct Vehicle {
     jobs: Vec<Job>,
     ride_tasks: Vec<RideTask>,
     job_buffer: Option<Job>,
+    reposition_target: Option<Coord>,
 }
 
 impl PartialEq for Vehicle {
@@ -89,7 +139,6 @@ impl PartialEq for Vehicle {
 impl Hash for Vehicle {
     fn hash<H: Hasher>(&self, state: &mut H) {
         state.write_i32(self.id);
-        state.finish();
     }
 }
 
@@ -100,6 +149,7 @@ impl Vehicle {
             jobs: Vec::<Job>::new(),
             ride_tasks: Vec::<RideTask>::new(),
             job_buffer: None,
+            reposition_target: None,
         }
     }
 
@@ -107,6 +157,10 @@ impl Vehicle {
         self.id
     }
 
+    fn invalid_state(&self, what: &'static str) -> Error {
+        Error::InvalidVehicleState(self.id as usize, what)
+    }
+
     fn current_task_mut(&mut self) -> Option<&mut RideTask> {
         match self.ride_tasks.len() {
             l if l > 0 => Some(&mut self.ride_tasks[l - 1]),
@@ -122,53 +176,62 @@ impl Vehicle {
         self.jobs.last()
     }
 
+    /// None while in transit, and for tasks that don't leave the vehicle anywhere in particular
     pub fn current_pos(&self) -> Option<Coord> {
         match self.current_task() {
             Some(t) => {
                 if t.is_idle() {
                     match t.task_type() {
                         RideTaskType::DrivingToStart | RideTaskType::WaitingAtStart => {
-                            return Some(self.current_job().unwrap().start());
+                            self.current_job().map(|j| j.start())
                         }
-                        RideTaskType::DrivingToEnd => {
-                            return Some(self.current_job().unwrap().end());
-                        }
-                        _ => unreachable!(),
-                    };
+                        RideTaskType::DrivingToEnd => self.current_job().map(|j| j.end()),
+                        RideTaskType::Repositioning => self.reposition_target,
+                        RideTaskType::Invalid => None,
+                    }
                 } else {
                     // no position when in transit
-                    return None;
+                    None
                 }
             }
             None => Some(Coord::default()), // origin if at start
         }
     }
 
+    /// Like `current_pos`, but also places the vehicle while in transit, going along x first
+    pub fn position(&self) -> Coord {
+        self.current_task()
+            .map_or(Coord::default(), |t| t.position())
+    }
+
     pub fn is_idle(&self) -> bool {
-        self.job_buffer.is_none()
-            && (self.ride_tasks.len() == 0 || self.current_task().unwrap().has_arrived_at_dest())
+        self.job_buffer.is_none() && self.is_ready_for_job()
     }
 
     fn add_job_task(
         &mut self,
+        job_id: JobId,
         job_start: Coord,
         job_end: Coord,
         job_earliest_start: TimeStep,
         current_step: TimeStep,
-    ) -> RideTaskType {
+    ) -> Result<RideTaskType, Error> {
         let mut task_type = RideTaskType::Invalid;
         let mut task_steps = 0;
+        let mut task_dest = job_start;
 
-        let cur_pos = self.current_pos().unwrap();
+        let cur_pos = self
+            .current_pos()
+            .ok_or_else(|| self.invalid_state("started a job while in transit"))?;
         let dist_to_end = cur_pos.dist(&job_end);
         let dist_to_start = cur_pos.dist(&job_start);
 
-        assert!(dist_to_start >= 0 && dist_to_end >= 0);
         {
+            let id = self.id;
             let mut set_task_params = |t, s| {
                 task_type = t;
                 task_steps = s;
-                //println!("Vehicle {} -> Task {:?}, Steps {}", self.id(), t, s);
+                trace!("Step {} | Vehicle {} -> Task {:?}, Steps {}", current_step, id, t, s);
             };
 
             if dist_to_start > 0 {
@@ -176,6 +239,7 @@ impl Vehicle {
             } else if dist_to_start == 0 {
                 if current_step >= job_earliest_start {
                     set_task_params(RideTaskType::DrivingToEnd, dist_to_end);
+                    task_dest = job_end;
                 } else {
                     set_task_params(
                         RideTaskType::WaitingAtStart,
@@ -184,96 +248,171 @@ impl Vehicle {
                 }
             } else if dist_to_end > 0 {
                 set_task_params(RideTaskType::DrivingToEnd, dist_to_end);
+                task_dest = job_end;
             } else {
-                // not sure if this ever happens in the input data (start_pos == end_pos for some pair of jobs)
-                unreachable!();
+                return Err(self.invalid_state("got a negative distance to its job"));
             }
         }
 
-        self.ride_tasks.push(RideTask::new(task_type, task_steps));
-        task_type
+        self.ride_tasks.push(RideTask::new(
+            task_type,
+            task_steps,
+            cur_pos,
+            task_dest,
+            Some(job_id),
+        ));
+        Ok(task_type)
+    }
+
+    /// When and where the vehicle is free to start its next job, None if it already has one
+    /// lined up or is busy otherwise. Vehicles driving a job become free at its end
+    pub fn availability(&self, current_step: TimeStep) -> Option<(TimeStep, Coord)> {
+        if self.job_buffer.is_some() {
+            return None;
+        }
+
+        match self.current_task() {
+            Some(t) if t.task_type() == RideTaskType::DrivingToEnd => self
+                .current_job()
+                .map(|j| (current_step + t.rem_steps, j.end())),
+            _ if self.is_idle() => self.current_pos().map(|p| (current_step, p)),
+            _ => None,
+        }
     }
 
-    pub fn queue_new_job(&mut self, job: Job) {
-        assert_eq!(self.job_buffer.is_none(), true);
+    /// Whether the job on the buffer can be started, jobs booked ahead wait for the current one to end
+    fn is_ready_for_job(&self) -> bool {
+        match self.current_task() {
+            Some(t) => t.has_arrived_at_dest() || t.has_repositioned(),
+            None => true,
+        }
+    }
+
+    /// Lines up the next job, which starts once the vehicle has dropped off its current one.
+    /// Fails if another one is lined up already
+    pub fn queue_new_job(&mut self, job: Job) -> Result<(), Error> {
+        if let Some(ref queued) = self.job_buffer {
+            return Err(Error::JobAlreadyQueued(self.id as usize, queued.id()));
+        }
         self.job_buffer = Some(job);
+        Ok(())
     }
 
-    pub fn tick(&mut self, current_step: TimeStep) -> TickComplete {
-        // load the job on the buffer, if any
-        if let Some(new_jerb) = self.job_buffer.take() {
-            self.add_job_task(
-                new_jerb.start(),
-                new_jerb.end(),
-                new_jerb.earliest_start(),
-                current_step,
-            );
-            self.jobs.push(new_jerb);
+    /// Sends an idle vehicle to the given position without a job, lingering there until at least `min_steps` have passed
+    pub fn reposition(&mut self, target: Coord, min_steps: TimeStep) -> Result<TimeStep, Error> {
+        let cur_pos = match self.current_pos() {
+            Some(p) if self.is_idle() => p,
+            _ => return Err(self.invalid_state("repositioned while busy")),
+        };
+        let steps = i32::max(cur_pos.dist(&target), min_steps);
+        if steps > 0 {
+            self.ride_tasks.push(RideTask::new(
+                RideTaskType::Repositioning,
+                steps,
+                cur_pos,
+                target,
+                None,
+            ));
+            self.reposition_target = Some(target);
+        }
+
+        Ok(steps)
+    }
+
+    /// Everything the vehicle got done in the step, in order
+    pub fn tick(&mut self, current_step: TimeStep) -> Result<Vec<TickComplete>, Error> {
+        let mut out = Vec::new();
+
+        // load the job on the buffer, if any, once the current one is done
+        if self.is_ready_for_job() {
+            if let Some(new_jerb) = self.job_buffer.take() {
+                // it may be picked up straight away, in which case it moves off in this step
+                if self.add_job_task(
+                    new_jerb.id(),
+                    new_jerb.start(),
+                    new_jerb.end(),
+                    new_jerb.earliest_start(),
+                    current_step,
+                )? == RideTaskType::DrivingToEnd
+                {
+                    out.push(TickComplete::JobStart(
+                        new_jerb.id(),
+                        new_jerb.dist(),
+                        new_jerb.earliest_start(),
+                    ));
+                }
+                self.jobs.push(new_jerb);
+            }
+        }
+
+        if self.ride_tasks.is_empty() {
+            // still parked at the origin
+            return Ok(out);
         }
 
-        assert!(self.ride_tasks.len() > 0);
-        let mut out = TickComplete::Continue;
-        let current_job_id = self.current_job().map(|j| j.id());
-        let current_job_start = self.current_job().map(|j| j.start());
-        let current_job_end = self.current_job().map(|j| j.end());
-        let current_job_dist = self.current_job().map(|j| j.dist());
-        let current_job_earliest_start = self.current_job().map(|j| j.earliest_start());
-        let current_job_latest_finish = self.current_job().map(|j| j.latest_finish());
+        let current_job = self.current_job().cloned();
+        let reposition_target = self.reposition_target;
+        let id = self.id as usize;
 
         if let Some(t) = self.current_task_mut() {
-            if !t.is_idle() {
-                if t.step() {
-                    if t.has_arrived_at_start() {
-                        // the job task will be updated next tick
-                        out = TickComplete::Continue;
-                    } else if t.is_done_waiting() {
-                        // same as above, the next job task will be updated next tick
-                        out = TickComplete::JobStart(
-                            current_job_id.unwrap(),
-                            current_job_dist.unwrap(),
-                            current_job_earliest_start.unwrap(),
-                        );
-                    } else if t.has_arrived_at_dest() {
-                        out = TickComplete::JobComplete(
-                            current_job_id.unwrap(),
-                            current_job_latest_finish.unwrap(),
-                            current_job_end.unwrap(),
-                        );
-                    } else {
-                        unreachable!()
-                    }
+            if !t.is_idle() && t.step(current_step).map_err(|e| Error::InvalidVehicleState(id, e))? {
+                if t.has_arrived_at_start() || t.is_done_waiting() {
+                    // the job task will be updated below
+                } else if t.has_arrived_at_dest() {
+                    let job = current_job
+                        .as_ref()
+                        .ok_or(Error::InvalidVehicleState(id, "arrived without a job"))?;
+                    out.push(TickComplete::JobComplete(
+                        job.id(),
+                        job.latest_finish(),
+                        job.end(),
+                    ));
+                } else if t.has_repositioned() {
+                    let target = reposition_target
+                        .ok_or(Error::InvalidVehicleState(id, "repositioned without a target"))?;
+                    out.push(TickComplete::RepositionComplete(target));
+                } else {
+                    return Err(Error::InvalidVehicleState(id, "finished a task of unknown type"));
                 }
             }
         }
 
         // strange control flow because borrow checker (tm)
-        if let Some(true) = self.current_task()
-            .map(|t| t.is_idle() && !t.has_arrived_at_dest())
+        if let Some(true) = self
+            .current_task()
+            .map(|t| t.is_idle() && !t.has_arrived_at_dest() && !t.has_repositioned())
+        {
+            if self.job_buffer.is_some() {
+                return Err(self.invalid_state("had a job lined up before reaching its current one"));
+            }
+            let job = current_job.ok_or_else(|| self.invalid_state("waited for a job it doesn't have"))?;
+
+            // account for cases where the waiting state is not available/is skipped
+            if self.add_job_task(
+                job.id(),
+                job.start(),
+                job.end(),
+                job.earliest_start(),
+                current_step,
+            )? == RideTaskType::DrivingToEnd
             {
-                assert_eq!(self.job_buffer, None);
-
-                // account for cases where the waiting state is not available/is skipped
-                match self.add_job_task(
-                    current_job_start.unwrap(),
-                    current_job_end.unwrap(),
-                    current_job_earliest_start.unwrap(),
-                    current_step,
-                ) {
-                    RideTaskType::DrivingToEnd => {
-                        out = TickComplete::JobStart(
-                            current_job_id.unwrap(),
-                            current_job_dist.unwrap(),
-                            current_job_earliest_start.unwrap(),
-                        );
-                    }
-                    _ => {}
-                };
+                out.push(TickComplete::JobStart(
+                    job.id(),
+                    job.dist(),
+                    job.earliest_start(),
+                ));
             }
+        }
 
-        out
+        Ok(out)
     }
 
     pub fn assigned_rides(&self) -> Vec<JobId> {
         self.jobs.iter().map(|t| t.id()).collect()
     }
+
+    /// Every task the vehicle has worked on so far, in order
+    pub fn segments(&self) -> Vec<Segment> {
+        self.ride_tasks.iter().filter_map(|t| t.segment()).collect()
+    }
 }
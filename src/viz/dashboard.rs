@@ -0,0 +1,156 @@
+use super::bounds;
+use scheduler::Problem;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+use util::{Coord, TimeStep};
+
+/// Darkest to brightest, for the density map
+const SHADES: &[u8] = b" .:-=+*#%@";
+
+/// Where the simulation stands, as shown on the dashboard
+#[derive(Clone, Debug)]
+pub struct Status {
+	pub step: TimeStep,
+	pub max_tsteps: TimeStep,
+	pub busy: usize,
+	pub idle: usize,
+	pub remaining_jobs: usize,
+	pub score: u64,
+	/// By vehicle ID, only filled in when the dashboard shows a map
+	pub positions: Vec<Coord>,
+}
+
+/// Coarse map of the grid, every character covering a block of intersections
+#[derive(Clone, Debug)]
+struct Map {
+	min: Coord,
+	max: Coord,
+	width: usize,
+	height: usize,
+}
+
+/// Live view of a running simulation that redraws the terminal with ANSI escape codes
+#[derive(Clone, Debug)]
+pub struct Dashboard {
+	interval: Duration,
+	map: Option<Map>,
+	started: Instant,
+	last_drawn: Option<Instant>,
+}
+
+impl Dashboard {
+	/// Redraws at most once per interval
+	pub fn new(interval: Duration) -> Dashboard {
+		Dashboard {
+			interval,
+			map: None,
+			started: Instant::now(),
+			last_drawn: None,
+		}
+	}
+
+	/// Also draws where the vehicles are, `width` characters across. Rows are squashed to half
+	/// as many characters as the grid's proportions call for, since characters are about twice as tall as wide
+	pub fn with_map(mut self, problem: &Problem, width: usize) -> Dashboard {
+		let (min, max) = bounds(problem);
+		let width = usize::max(width, 1);
+		let aspect = f64::from(max.x - min.x + 1) / f64::from(max.y - min.y + 1);
+		let height = usize::max((width as f64 * aspect / 2.0).round() as usize, 1);
+
+		self.map = Some(Map {
+			min,
+			max,
+			width,
+			height,
+		});
+		self
+	}
+
+	pub fn wants_positions(&self) -> bool {
+		self.map.is_some()
+	}
+
+	/// Whether it's time for the next redraw
+	pub fn is_due(&self) -> bool {
+		self.last_drawn
+			.is_none_or(|t| t.elapsed() >= self.interval)
+	}
+
+	/// Dashboard contents, without the escape codes
+	pub fn render(&self, status: &Status) -> String {
+		let progress = f64::from(status.step) / f64::from(i32::max(status.max_tsteps, 1));
+		let elapsed = self.started.elapsed().as_secs_f64();
+		let eta = if status.step > 0 {
+			format_duration(elapsed * f64::from(status.max_tsteps - status.step) / f64::from(status.step))
+		} else {
+			"--:--:--".to_string()
+		};
+
+		let mut out = format!(
+			"Step: {} / {} ({:.1}%) | Elapsed: {} | Remaining: {}\n",
+			status.step,
+			status.max_tsteps,
+			progress * 100.0,
+			format_duration(elapsed),
+			eta
+		);
+		out += &format!("Vehicles: {} busy, {} idle\n", status.busy, status.idle);
+		out += &format!(
+			"Remaining jobs: {} | Score: {}\n",
+			status.remaining_jobs, status.score
+		);
+
+		if let Some(ref map) = self.map {
+			out += &map.render(&status.positions);
+		}
+
+		out
+	}
+
+	/// Clears the terminal and draws the status, on stderr so it stays out of a solution written to stdout
+	pub fn draw(&mut self, status: &Status) {
+		self.last_drawn = Some(Instant::now());
+
+		let stderr = io::stderr();
+		let mut out = stderr.lock();
+		// clear the screen and move the cursor to the top left corner
+		let _ = write!(out, "\x1b[2J\x1b[H{}", self.render(status));
+		let _ = out.flush();
+	}
+}
+
+impl Map {
+	fn render(&self, positions: &[Coord]) -> String {
+		let mut counts = vec![0usize; self.width * self.height];
+		let rows = (self.max.x - self.min.x + 1) as usize;
+		let cols = (self.max.y - self.min.y + 1) as usize;
+		for p in positions {
+			let row = (p.x - self.min.x) as usize * self.height / rows;
+			let col = (p.y - self.min.y) as usize * self.width / cols;
+			if row < self.height && col < self.width {
+				counts[row * self.width + col] += 1;
+			}
+		}
+
+		let max = counts.iter().cloned().max().unwrap_or(0).max(1);
+		let border = format!("+{}+\n", "-".repeat(self.width));
+		let mut out = border.clone();
+		for row in counts.chunks(self.width) {
+			out += "|";
+			for &c in row {
+				// rounded up, so that anything at all shows up even next to the busiest block
+				let shade = (c * (SHADES.len() - 1)).div_ceil(max);
+				out.push(SHADES[shade] as char);
+			}
+			out += "|\n";
+		}
+		out += &border;
+
+		out
+	}
+}
+
+fn format_duration(secs: f64) -> String {
+	let secs = secs.round() as u64;
+	format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
@@ -0,0 +1,116 @@
+use scheduler::{Job, JobId, Problem};
+use solution::Solution;
+use std::collections::HashSet;
+use util::{Coord, FileIOError, FileWriter, TimeStep};
+
+/// Which part of the compatibility graph to export, everything is included by default
+#[derive(Clone, Debug, Default)]
+pub struct GraphFilter {
+	/// Rides whose [earliest start, latest finish] overlaps this window
+	pub window: Option<(TimeStep, TimeStep)>,
+	/// Rides starting within this rectangle, given by opposite corners
+	pub region: Option<(Coord, Coord)>,
+	/// Edges that need longer waits than this are left out
+	pub max_wait: Option<TimeStep>,
+}
+
+impl GraphFilter {
+	fn includes(&self, job: &Job) -> bool {
+		let in_window = self
+			.window
+			.is_none_or(|(from, to)| job.earliest_start() <= to && job.latest_finish() >= from);
+		let in_region = self.region.is_none_or(|(a, b)| {
+			let (x, y) = (job.start().x, job.start().y);
+			i32::min(a.x, b.x) <= x && x <= i32::max(a.x, b.x) && i32::min(a.y, b.y) <= y && y <= i32::max(a.y, b.y)
+		});
+
+		in_window && in_region
+	}
+}
+
+/// Empty driving and waiting between the two rides when the first one is served as early as
+/// possible, None if the second can't be served on time after the first
+fn follow(problem: &Problem, a: &Job, b: &Job) -> Option<(i32, TimeStep)> {
+	let deadhead = a.end().dist(&b.start());
+	let arrival = a.earliest_start() + a.dist() + deadhead;
+	let start = TimeStep::max(arrival, b.earliest_start());
+	if start + b.dist() > TimeStep::min(b.latest_finish(), problem.max_tsteps()) {
+		None
+	} else {
+		Some((deadhead, start - arrival))
+	}
+}
+
+/// The "ride j can follow ride i" graph in Graphviz DOT format. Edges are labelled with the
+/// empty driving and waiting in between, the rides and hand-overs of the solution are highlighted
+pub fn compatibility_dot(problem: &Problem, filter: &GraphFilter, solution: Option<&Solution>) -> String {
+	let rides: Vec<&Job> = problem.jobs().iter().filter(|j| filter.includes(j)).collect();
+
+	let mut used: HashSet<JobId> = HashSet::new();
+	let mut route_edges: HashSet<(JobId, JobId)> = HashSet::new();
+	for route in solution.iter().flat_map(|s| s.rides().iter()) {
+		used.extend(route.iter().cloned());
+		route_edges.extend(route.windows(2).map(|w| (w[0], w[1])));
+	}
+
+	let mut out = String::from("digraph rides {\n\trankdir=LR;\n\tnode [shape=box, fontsize=10];\n\tedge [fontsize=8];\n");
+	for job in rides.iter() {
+		let highlight = if used.contains(&job.id()) {
+			", style=filled, fillcolor=lightblue"
+		} else {
+			""
+		};
+		out += &format!(
+			"\t{} [label=\"{}\\n({}, {}) -> ({}, {})\\n[{}, {}]\"{}];\n",
+			job.id(),
+			job.id(),
+			job.start().x,
+			job.start().y,
+			job.end().x,
+			job.end().y,
+			job.earliest_start(),
+			job.latest_finish(),
+			highlight
+		);
+	}
+
+	for a in rides.iter() {
+		for b in rides.iter() {
+			if a.id() == b.id() {
+				continue;
+			}
+
+			let on_route = route_edges.contains(&(a.id(), b.id()));
+			let (deadhead, wait) = match follow(problem, a, b) {
+				Some((d, w)) if on_route || filter.max_wait.is_none_or(|max| w <= max) => (d, w),
+				// a late hand-over of the solution is still worth seeing
+				None if on_route => (a.end().dist(&b.start()), 0),
+				_ => continue,
+			};
+
+			let highlight = if on_route { ", color=red, penwidth=2" } else { "" };
+			out += &format!(
+				"\t{} -> {} [label=\"d={} w={}\"{}];\n",
+				a.id(),
+				b.id(),
+				deadhead,
+				wait,
+				highlight
+			);
+		}
+	}
+	out += "}\n";
+
+	out
+}
+
+pub fn write_dot(
+	problem: &Problem,
+	filter: &GraphFilter,
+	solution: Option<&Solution>,
+	path: &str,
+) -> Result<(), FileIOError> {
+	let mut writer = FileWriter::new(path)?;
+	writer.write_line(&compatibility_dot(problem, filter, solution))?;
+	writer.finish()
+}
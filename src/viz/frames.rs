@@ -0,0 +1,177 @@
+use super::{bounds, colour};
+use scheduler::{JobId, Problem, SegmentKind};
+use solution::{route_timings, Solution};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use util::{Coord, FileIOError, FileWriter, TimeStep};
+
+const MAP_SIZE: f64 = 800.0;
+const HEADER_HEIGHT: f64 = 24.0;
+
+/// Stretch of a route on which the vehicle goes from one point to another, or waits if they're the same
+struct Leg {
+	kind: SegmentKind,
+	from: Coord,
+	to: Coord,
+	begin: TimeStep,
+	end: TimeStep,
+}
+
+impl Leg {
+	fn position(&self, step: TimeStep) -> Coord {
+		self.from.towards(&self.to, step - self.begin)
+	}
+
+	/// Unit step the vehicle takes next, none when waiting or there
+	fn heading(&self, step: TimeStep) -> (i32, i32) {
+		let pos = self.position(step);
+		if pos.x != self.to.x {
+			((self.to.x - pos.x).signum(), 0)
+		} else {
+			(0, (self.to.y - pos.y).signum())
+		}
+	}
+}
+
+/// Every leg of the route, as the scorer times it
+fn legs(problem: &Problem, route: &[JobId]) -> Vec<Leg> {
+	let mut pos = Coord::default();
+	let mut out = Vec::new();
+	for t in route_timings(problem, route) {
+		let job = problem.job(t.id);
+		out.push(Leg {
+			kind: SegmentKind::Deadhead,
+			from: pos,
+			to: job.start(),
+			begin: t.departure,
+			end: t.arrival,
+		});
+		out.push(Leg {
+			kind: SegmentKind::Waiting,
+			from: job.start(),
+			to: job.start(),
+			begin: t.arrival,
+			end: t.start,
+		});
+		out.push(Leg {
+			kind: SegmentKind::OnRide,
+			from: job.start(),
+			to: job.end(),
+			begin: t.start,
+			end: t.finish,
+		});
+		pos = job.end();
+	}
+
+	out
+}
+
+/// A vehicle's position, heading and what it's up to at the given step. Vehicles stay
+/// wherever their last ride ends, None for the kind when idle
+fn state(legs: &[Leg], step: TimeStep) -> (Coord, (i32, i32), Option<SegmentKind>) {
+	match legs.iter().find(|l| step < l.end) {
+		Some(leg) => (leg.position(step), leg.heading(step), Some(leg.kind)),
+		None => (legs.last().map_or(Coord::default(), |l| l.to), (0, 0), None),
+	}
+}
+
+/// Timings of a solution, worked out once for all its frames
+struct Replay {
+	routes: Vec<Vec<Leg>>,
+	started: HashMap<JobId, TimeStep>,
+}
+
+impl Replay {
+	fn new(problem: &Problem, solution: &Solution) -> Replay {
+		Replay {
+			routes: solution.rides().iter().map(|r| legs(problem, r)).collect(),
+			started: solution
+				.rides()
+				.iter()
+				.flat_map(|r| route_timings(problem, r))
+				.map(|t| (t.id, t.start))
+				.collect(),
+		}
+	}
+}
+
+/// SVG snapshot of the solution at the given step, rows down and columns across. Vehicles are
+/// dots with a tick pointing where they're headed, coloured as in the Gantt chart. Rides that
+/// haven't been picked up yet and can still be served are grey squares at their start
+pub fn frame_svg(problem: &Problem, solution: &Solution, step: TimeStep) -> String {
+	frame(problem, &Replay::new(problem, solution), step)
+}
+
+fn frame(problem: &Problem, replay: &Replay, step: TimeStep) -> String {
+	let (min, max) = bounds(problem);
+	let scale = MAP_SIZE / f64::from(i32::max(max.x - min.x, max.y - min.y) + 1);
+	let centre = |c: Coord| {
+		(
+			(f64::from(c.y - min.y) + 0.5) * scale,
+			HEADER_HEIGHT + (f64::from(c.x - min.x) + 0.5) * scale,
+		)
+	};
+	let width = f64::from(max.y - min.y + 1) * scale;
+	let height = HEADER_HEIGHT + f64::from(max.x - min.x + 1) * scale;
+	let radius = f64::max(scale * 0.4, 2.0);
+
+	let mut out = format!(
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" font-family=\"sans-serif\" font-size=\"12\">\n",
+		width, height
+	);
+	out += &format!("<rect width=\"{:.0}\" height=\"{:.0}\" fill=\"white\"/>\n", width, height);
+	out += &format!("<text x=\"4\" y=\"16\">step {} / {}</text>\n", step, problem.max_tsteps());
+
+	for job in problem.jobs() {
+		let picked_up = replay.started.get(&job.id()).is_some_and(|&s| s <= step);
+		if picked_up || step + job.dist() > job.latest_finish() {
+			continue;
+		}
+
+		let (cx, cy) = centre(job.start());
+		out += &format!(
+			"<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#bbbbbb\"/>\n",
+			cx - radius / 2.0,
+			cy - radius / 2.0,
+			radius,
+			radius
+		);
+	}
+
+	for legs in replay.routes.iter() {
+		let (pos, (dx, dy), kind) = state(legs, step);
+		let (cx, cy) = centre(pos);
+		let fill = kind.map_or("#606060", colour);
+		out += &format!("<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"{}\"/>\n", cx, cy, radius, fill);
+		if (dx, dy) != (0, 0) {
+			out += &format!(
+				"<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"black\"/>\n",
+				cx,
+				cy,
+				cx + f64::from(dy) * radius * 2.0,
+				cy + f64::from(dx) * radius * 2.0
+			);
+		}
+	}
+	out += "</svg>\n";
+
+	out
+}
+
+/// Writes a frame every `every` steps from 0 to the end of the simulation into the directory,
+/// as frame_00000.svg, frame_00001.svg.., and returns how many
+pub fn write_frames(problem: &Problem, solution: &Solution, every: TimeStep, dir: &str) -> Result<usize, FileIOError> {
+	fs::create_dir_all(dir).map_err(FileIOError::CouldntOpenFile)?;
+
+	let replay = Replay::new(problem, solution);
+	let steps = (0..=problem.max_tsteps()).step_by(i32::max(every, 1) as usize);
+	for (idx, step) in steps.clone().enumerate() {
+		let path = Path::new(dir).join(format!("frame_{:05}.svg", idx));
+		let mut writer = FileWriter::new(path.to_str().unwrap())?;
+		writer.write_line(&frame(problem, &replay, step))?;
+		writer.finish()?;
+	}
+
+	Ok(steps.count())
+}
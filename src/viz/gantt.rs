@@ -0,0 +1,106 @@
+use super::colour;
+use scheduler::{Problem, Segment, SegmentKind};
+use util::{FileIOError, FileWriter, TimeStep};
+
+const LABEL_WIDTH: f64 = 50.0;
+const CHART_WIDTH: f64 = 1200.0;
+const ROW_HEIGHT: f64 = 12.0;
+const HEADER_HEIGHT: f64 = 40.0;
+
+/// Roughly ten ticks along the time axis, on round numbers
+fn tick_interval(max_tsteps: TimeStep) -> TimeStep {
+	let rough = i32::max(max_tsteps / 10, 1);
+	let magnitude = 10i32.pow((rough as f64).log10().floor() as u32);
+	[1, 2, 5, 10]
+		.iter()
+		.map(|m| m * magnitude)
+		.find(|&i| i >= rough)
+		.unwrap()
+}
+
+/// SVG Gantt chart with a row per vehicle and simulation steps along the x axis.
+/// Drop-offs are marked green when on time and red when late, gaps are idle time
+pub fn gantt_svg(problem: &Problem, segments: &[Vec<Segment>]) -> String {
+	let max_tsteps = i32::max(problem.max_tsteps(), 1);
+	let scale = CHART_WIDTH / max_tsteps as f64;
+	let x = |t: TimeStep| LABEL_WIDTH + t as f64 * scale;
+	let width = LABEL_WIDTH + CHART_WIDTH + 10.0;
+	let height = HEADER_HEIGHT + segments.len() as f64 * ROW_HEIGHT + 10.0;
+
+	let mut out = format!(
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"sans-serif\" font-size=\"9\">\n",
+		width, height
+	);
+	out += &format!("<rect width=\"{}\" height=\"{}\" fill=\"white\"/>\n", width, height);
+
+	let legend = [
+		("deadhead", colour(SegmentKind::Deadhead)),
+		("waiting", colour(SegmentKind::Waiting)),
+		("on ride", colour(SegmentKind::OnRide)),
+		("repositioning", colour(SegmentKind::Repositioning)),
+		("on time", "#008000"),
+		("late", "#d00000"),
+	];
+	for (idx, (name, fill)) in legend.iter().enumerate() {
+		let lx = LABEL_WIDTH + idx as f64 * 100.0;
+		out += &format!(
+			"<rect x=\"{}\" y=\"6\" width=\"10\" height=\"10\" fill=\"{}\"/><text x=\"{}\" y=\"15\">{}</text>\n",
+			lx,
+			fill,
+			lx + 14.0,
+			name
+		);
+	}
+
+	let interval = tick_interval(max_tsteps);
+	for t in (0..=max_tsteps).step_by(interval as usize) {
+		out += &format!(
+			"<line x1=\"{0}\" y1=\"{1}\" x2=\"{0}\" y2=\"{2}\" stroke=\"#dddddd\"/><text x=\"{0}\" y=\"{3}\" text-anchor=\"middle\">{4}</text>\n",
+			x(t),
+			HEADER_HEIGHT - 4.0,
+			height - 10.0,
+			HEADER_HEIGHT - 8.0,
+			t
+		);
+	}
+
+	for (v, row) in segments.iter().enumerate() {
+		let y = HEADER_HEIGHT + v as f64 * ROW_HEIGHT;
+		out += &format!("<text x=\"4\" y=\"{}\">{}</text>\n", y + ROW_HEIGHT - 3.0, v);
+
+		for seg in row.iter().filter(|s| s.to > s.from) {
+			let job = seg.job.map_or(String::new(), |j| format!("job {}, ", j));
+			out += &format!(
+				"<rect x=\"{:.2}\" y=\"{}\" width=\"{:.2}\" height=\"{}\" fill=\"{}\"><title>{:?}, {}{}-{}</title></rect>\n",
+				x(seg.from),
+				y + 1.0,
+				(seg.to - seg.from) as f64 * scale,
+				ROW_HEIGHT - 2.0,
+				colour(seg.kind),
+				seg.kind,
+				job,
+				seg.from,
+				seg.to
+			);
+
+			if let (SegmentKind::OnRide, Some(id)) = (seg.kind, seg.job) {
+				let late = seg.to > problem.job(id).latest_finish();
+				out += &format!(
+					"<circle cx=\"{:.2}\" cy=\"{}\" r=\"2.5\" fill=\"{}\"/>\n",
+					x(seg.to),
+					y + ROW_HEIGHT / 2.0,
+					if late { "#d00000" } else { "#008000" }
+				);
+			}
+		}
+	}
+	out += "</svg>\n";
+
+	out
+}
+
+pub fn write_gantt(problem: &Problem, segments: &[Vec<Segment>], path: &str) -> Result<(), FileIOError> {
+	let mut writer = FileWriter::new(path)?;
+	writer.write_line(&gantt_svg(problem, segments))?;
+	writer.finish()
+}
@@ -0,0 +1,144 @@
+use super::bounds;
+use scheduler::Problem;
+use solution::{route_timings, Solution};
+use std::collections::HashSet;
+use util::{Coord, FileIOError, FileWriter};
+
+/// What gets counted
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Density {
+	/// Ride start intersections
+	Starts,
+	/// Ride end intersections
+	Ends,
+	/// Start intersections of the rides a solution doesn't score
+	Missed,
+}
+
+/// Counts over the grid, binned into squares of `bin` by `bin` intersections.
+/// Rows run down the image and columns across it
+#[derive(Clone, Debug)]
+pub struct Heatmap {
+	/// Intersection in the top left corner
+	origin: Coord,
+	bin: i32,
+	width: usize,
+	height: usize,
+	counts: Vec<u32>,
+}
+
+impl Heatmap {
+	/// Empty map covering the whole grid, and every ride should any lie outside of it
+	pub fn new(problem: &Problem, bin: i32) -> Heatmap {
+		let bin = i32::max(bin, 1);
+		let (min, max) = bounds(problem);
+		let height = ((max.x - min.x) / bin + 1) as usize;
+		let width = ((max.y - min.y) / bin + 1) as usize;
+		Heatmap {
+			origin: min,
+			bin,
+			width,
+			height,
+			counts: vec![0; width * height],
+		}
+	}
+
+	/// Rides missed are those the solution leaves out or serves too late
+	pub fn density(problem: &Problem, kind: Density, solution: Option<&Solution>, bin: i32) -> Heatmap {
+		let mut out = Heatmap::new(problem, bin);
+
+		let scored: HashSet<i32> = solution
+			.iter()
+			.flat_map(|s| s.rides().iter())
+			.flat_map(|r| route_timings(problem, r))
+			.filter(|t| t.on_time())
+			.map(|t| t.id)
+			.collect();
+
+		for job in problem.jobs() {
+			match kind {
+				Density::Starts => out.add(job.start()),
+				Density::Ends => out.add(job.end()),
+				Density::Missed if !scored.contains(&job.id()) => out.add(job.start()),
+				Density::Missed => {}
+			}
+		}
+
+		out
+	}
+
+	pub fn add(&mut self, c: Coord) {
+		let row = (c.x - self.origin.x) / self.bin;
+		let col = (c.y - self.origin.y) / self.bin;
+		if row >= 0 && col >= 0 && (row as usize) < self.height && (col as usize) < self.width {
+			self.counts[row as usize * self.width + col as usize] += 1;
+		}
+	}
+
+	pub fn width(&self) -> usize {
+		self.width
+	}
+
+	pub fn height(&self) -> usize {
+		self.height
+	}
+
+	pub fn count(&self, row: usize, col: usize) -> u32 {
+		self.counts[row * self.width + col]
+	}
+
+	pub fn max(&self) -> u32 {
+		self.counts.iter().cloned().max().unwrap_or(0)
+	}
+
+	/// Every bin scaled to [0, 255] against the busiest one
+	fn levels(&self) -> Vec<u8> {
+		let max = u32::max(self.max(), 1) as u64;
+		self.counts
+			.iter()
+			.map(|&c| (c as u64 * 255 / max) as u8)
+			.collect()
+	}
+
+	fn rows<F>(&self, pixel: F) -> String
+	where
+		F: Fn(u8) -> String,
+	{
+		let levels = self.levels();
+		let mut out = String::new();
+		for row in levels.chunks(self.width) {
+			out += &row.iter().map(|&l| pixel(l)).collect::<Vec<_>>().join(" ");
+			out += "\n";
+		}
+
+		out
+	}
+
+	/// Plain (ASCII) PGM, black where nothing happens
+	pub fn to_pgm(&self) -> String {
+		format!("P2\n{} {}\n255\n", self.width, self.height) + &self.rows(|l| l.to_string())
+	}
+
+	/// Plain (ASCII) PPM, going from black through red and yellow to white
+	pub fn to_ppm(&self) -> String {
+		let hot = |l: u8| {
+			let l = l as u32 * 3;
+			let (r, g, b) = (u32::min(l, 255), l.saturating_sub(255).min(255), l.saturating_sub(510));
+			format!("{} {} {}", r, g, b)
+		};
+
+		format!("P3\n{} {}\n255\n", self.width, self.height) + &self.rows(hot)
+	}
+}
+
+pub fn write_pgm(heatmap: &Heatmap, path: &str) -> Result<(), FileIOError> {
+	let mut writer = FileWriter::new(path)?;
+	writer.write_line(heatmap.to_pgm().trim_end())?;
+	writer.finish()
+}
+
+pub fn write_ppm(heatmap: &Heatmap, path: &str) -> Result<(), FileIOError> {
+	let mut writer = FileWriter::new(path)?;
+	writer.write_line(heatmap.to_ppm().trim_end())?;
+	writer.finish()
+}
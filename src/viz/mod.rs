@@ -0,0 +1,29 @@
+use scheduler::{Problem, SegmentKind};
+use util::Coord;
+
+pub mod dashboard;
+pub mod dot;
+pub mod frames;
+pub mod gantt;
+pub mod heatmap;
+
+fn colour(kind: SegmentKind) -> &'static str {
+	match kind {
+		SegmentKind::Deadhead => "#f0a030",
+		SegmentKind::Waiting => "#f0e040",
+		SegmentKind::OnRide => "#40a050",
+		SegmentKind::Repositioning => "#a080d0",
+	}
+}
+
+/// Opposite corners of the grid, stretched to take in any ride that lies outside of it
+fn bounds(problem: &Problem) -> (Coord, Coord) {
+	let coords = problem.jobs().iter().flat_map(|j| vec![j.start(), j.end()]);
+	let (mut min, mut max) = (Coord::default(), Coord::new(problem.num_rows() - 1, problem.num_cols() - 1));
+	for c in coords {
+		min = Coord::new(i32::min(min.x, c.x), i32::min(min.y, c.y));
+		max = Coord::new(i32::max(max.x, c.x), i32::max(max.y, c.y));
+	}
+
+	(min, max)
+}
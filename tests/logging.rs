@@ -0,0 +1,38 @@
+//! The log level is global to the process, so this runs on its own rather than alongside the
+//! simulations in the main test suite
+
+#[macro_use]
+extern crate google_hashcode18_prelim as root;
+
+use root::logging::{self, Level};
+
+#[test]
+fn log_levels() {
+	assert_eq!("debug".parse::<Level>(), Ok(Level::Debug));
+	assert_eq!("WARN".parse::<Level>(), Ok(Level::Warn));
+	assert!("loud".parse::<Level>().is_err());
+	assert_eq!(Level::Trace.to_string(), "trace");
+	assert!(Level::Error < Level::Warn && Level::Debug < Level::Trace);
+
+	// info until told otherwise
+	assert_eq!(logging::max_level(), Level::Info);
+	assert!(logging::enabled(Level::Warn) && !logging::enabled(Level::Debug));
+
+	// disabled messages don't even evaluate their arguments
+	let mut evaluated = 0;
+	debug!("{}", {
+		evaluated += 1;
+		evaluated
+	});
+	assert_eq!(evaluated, 0);
+
+	logging::set_max_level(Level::Error);
+	assert!(!logging::enabled(Level::Warn));
+	logging::set_max_level(Level::Trace);
+	trace!("{}", {
+		evaluated += 1;
+		evaluated
+	});
+	assert_eq!(evaluated, 1);
+	logging::set_max_level(Level::Info);
+}
@@ -1,10 +1,1222 @@
 extern crate google_hashcode18_prelim as root;
 
-use root::scheduler::JobScheduler;
-use root::util::{FileIOError, FileReader, FileWriter};
+use root::batch::{input_files, run_batch, BatchItem};
+use root::error::Error;
+use root::benchmark::{self, BenchmarkError};
+use root::lp::{self, LpError, Slice};
+use root::portfolio::{run_portfolio, EntryOutcome, PortfolioEntry};
+use root::rng::Rng;
+use root::scheduler::{DispatchStrategy, Job, JobScheduler, Observer, Problem, RepositionPolicy, Segment, SegmentKind};
+use root::solution::{route_score, route_timings, Solution, SolutionError, WriteOutcome};
+use root::solver::routes::Routes;
+use root::solver::{self, ExactError, ExactParams, GaParams, LnsParams, Solver, TabuParams};
+use root::util::{Coord, FileIOError, FileReader, FileWriter};
+use root::viz::dashboard::{Dashboard, Status};
+use root::viz::dot::{self, GraphFilter};
+use root::viz::frames;
+use root::viz::gantt;
+use root::viz::heatmap::{Density, Heatmap};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::error::Error as StdError;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+use std::time::Duration;
+
+fn load(path: &str) -> JobScheduler {
+	JobScheduler::new(FileReader::new(path).expect("Couldn't open input file")).expect("Couldn't parse input")
+}
+
+fn load_problem(path: &str) -> Problem {
+	Problem::new(FileReader::new(path).expect("Couldn't open input file")).expect("Couldn't parse input")
+}
+
+fn assigned_rides(output: &str) -> Vec<i32> {
+	output
+		.lines()
+		.flat_map(|l| l.split(' ').skip(1).map(|r| r.parse::<i32>().unwrap()))
+		.collect()
+}
 
 #[cfg(test)]
 #[test]
 fn example_test() {
 	assert_eq!(true, true);
 }
+
+#[test]
+fn missing_input_file() {
+	match FileReader::new("data/does_not_exist.in") {
+		Err(FileIOError::CouldntOpenFile(_)) => {}
+		_ => panic!("expected CouldntOpenFile"),
+	}
+}
+
+#[test]
+fn write_output_round_trip() {
+	let mut runner = load("data/a_example.in");
+	runner.run().unwrap();
+
+	let path = ::std::env::temp_dir().join("hashcode_a_example.o");
+	{
+		let mut output = FileWriter::new(path.to_str().unwrap()).unwrap();
+		runner.write_output(&mut output).unwrap();
+	}
+
+	let lines = FileReader::new(path.to_str().unwrap())
+		.unwrap()
+		.read_all_lines()
+		.unwrap();
+	assert_eq!(lines.join("\n") + "\n", runner.output_as_str());
+}
+
+/// Writer into a buffer the test can still look at once the `FileWriter` owns it
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.0.borrow_mut().extend_from_slice(buf);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+#[test]
+fn in_memory_io() {
+	let input = ::std::fs::read_to_string("data/a_example.in").unwrap();
+	let mut runner = JobScheduler::new(FileReader::from_string(&input)).unwrap();
+	runner.run().unwrap();
+	let mut from_file = load("data/a_example.in");
+	from_file.run().unwrap();
+	assert_eq!(runner.output_as_str(), from_file.output_as_str());
+
+	let mut raw = Vec::new();
+	runner.write_output(&mut raw).unwrap();
+	assert_eq!(String::from_utf8(raw).unwrap(), runner.output_as_str());
+
+	let buffer = SharedBuffer::default();
+	let mut output = FileWriter::from_writer(buffer.clone());
+	runner.write_output(&mut output).unwrap();
+	output.finish().unwrap();
+	assert_eq!(String::from_utf8(buffer.0.borrow().clone()).unwrap(), runner.output_as_str());
+
+	let lines = FileReader::from_reader(io::Cursor::new(runner.output_as_str().into_bytes()))
+		.read_all_lines()
+		.unwrap();
+	assert_eq!(Solution::from_lines(&lines), Ok(runner.solution()));
+
+	let piped = BatchItem::new(PathBuf::from("-"));
+	assert!(piped.writes_stdout());
+	assert!(!BatchItem::new(PathBuf::from("data/a_example.in")).writes_stdout());
+}
+
+#[test]
+fn atomic_solution_output() {
+	let problem = load_problem("data/a_example.in");
+	let path = ::std::env::temp_dir().join("hashcode_atomic.o");
+	let path = path.to_str().unwrap();
+	let _ = ::std::fs::remove_file(path);
+
+	let worse = Solution::new(vec![vec![0], vec![]]);
+	let better = Solution::new(vec![vec![0], vec![2, 1]]);
+	assert_eq!(worse.write_unless_worse(&problem, path).unwrap(), WriteOutcome::Written);
+	assert_eq!(better.write_unless_worse(&problem, path).unwrap(), WriteOutcome::Written);
+	assert_eq!(worse.write_unless_worse(&problem, path).unwrap(), WriteOutcome::Kept(10));
+
+	let lines = FileReader::new(path).unwrap().read_all_lines().unwrap();
+	assert_eq!(Solution::from_lines(&lines), Ok(better.clone()));
+	// nothing is left over from writing it
+	let leftovers = ::std::fs::read_dir(::std::env::temp_dir())
+		.unwrap()
+		.filter(|e| e.as_ref().unwrap().file_name().to_string_lossy().starts_with("hashcode_atomic.o."))
+		.count();
+	assert_eq!(leftovers, 0);
+
+	// plain writes replace whatever is there
+	worse.write(path).unwrap();
+	let lines = FileReader::new(path).unwrap().read_all_lines().unwrap();
+	assert_eq!(Solution::from_lines(&lines), Ok(worse));
+	::std::fs::remove_file(path).unwrap();
+
+	let malformed = vec!["1 0".to_string(), "2 1".to_string()];
+	assert_eq!(Solution::from_lines(&malformed), Err(SolutionError::MalformedLine(2)));
+
+	// the temporary file goes away again when the target can't be replaced
+	let dir = ::std::env::temp_dir().join("hashcode_atomic_dir");
+	::std::fs::create_dir_all(dir.join("occupied")).unwrap();
+	let target = dir.join("occupied");
+	match better.write(target.to_str().unwrap()) {
+		Err(FileIOError::RenameError(_)) => {}
+		r => panic!("expected RenameError, got {:?}", r),
+	}
+	assert_eq!(::std::fs::read_dir(&dir).unwrap().count(), 1);
+	::std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn repositioning_assigns_rides_once() {
+	for policy in &[
+		RepositionPolicy::Stay,
+		RepositionPolicy::TowardDemand(1),
+		RepositionPolicy::TowardDemand(100),
+	] {
+		let mut runner = load("data/b_should_be_easy.in");
+		runner.set_reposition_policy(*policy);
+		runner.run().unwrap();
+
+		let rides = assigned_rides(&runner.output_as_str());
+		let unique: HashSet<i32> = rides.iter().cloned().collect();
+		assert_eq!(rides.len(), unique.len());
+		assert!(runner.calculate_score() > 0);
+	}
+}
+
+#[test]
+fn repositioning_waits_for_bonus() {
+	// ride 1 starts where the vehicle is parked, but only comes into view once the vehicle would be busy with
+	// ride 0, which misses its bonus either way
+	let lines = vec![
+		"1 20 1 2 100 100".to_string(),
+		"0 1 0 11 0 50".to_string(),
+		"0 0 0 10 20 50".to_string(),
+	];
+	let problem = Problem::from_lines(&lines).unwrap();
+
+	let mut scores = Vec::new();
+	for policy in &[RepositionPolicy::Stay, RepositionPolicy::TowardDemand(50)] {
+		let mut runner = JobScheduler::from_problem(&problem);
+		runner.set_dispatch_strategy(DispatchStrategy::BonusAware {
+			bonus_weight: 1.0,
+			horizon: 5,
+		});
+		runner.set_reposition_policy(*policy);
+		runner.clear_observers();
+		runner.run().unwrap();
+		scores.push(runner.solution().score(&problem).unwrap());
+	}
+
+	// staying put means taking ride 0 and being too late for ride 1, repositioning picks up ride 1 with the
+	// bonus and still has time for ride 0 after it
+	assert_eq!(scores, vec![10, 120]);
+}
+
+#[test]
+fn bonus_aware_dispatch() {
+	let mut funky = load("data/b_should_be_easy.in");
+	funky.run().unwrap();
+
+	let mut bonus_aware = load("data/b_should_be_easy.in");
+	bonus_aware.set_dispatch_strategy(DispatchStrategy::BonusAware {
+		bonus_weight: 1.0,
+		horizon: 20000,
+	});
+	bonus_aware.run().unwrap();
+
+	let rides = assigned_rides(&bonus_aware.output_as_str());
+	let unique: HashSet<i32> = rides.iter().cloned().collect();
+	assert_eq!(rides.len(), unique.len());
+	assert!(bonus_aware.calculate_score() >= funky.calculate_score());
+}
+
+#[test]
+fn book_ahead_dispatch() {
+	// the second ride starts where the first one ends
+	let lines = vec![
+		"1 10 1 2 0 20".to_string(),
+		"0 2 0 6 0 20".to_string(),
+		"0 6 0 9 0 20".to_string(),
+	];
+	let problem = Problem::from_lines(&lines).unwrap();
+
+	for &book_ahead in [false, true].iter() {
+		let mut runner = JobScheduler::from_problem(&problem);
+		runner.set_book_ahead(book_ahead);
+		runner.clear_observers();
+
+		// the vehicle picks up the first ride in step 3 and drops it off in step 7
+		while runner.current_step() < 3 {
+			runner.step().unwrap();
+		}
+		let remaining = if book_ahead { 0 } else { 1 };
+		assert!(runner.summary().starts_with(&format!("Remaining jobs: {} ", remaining)));
+
+		while runner.step().unwrap() {}
+		// a lone vehicle gets to its next ride just as fast without booking ahead
+		assert_eq!(runner.solution(), Solution::new(vec![vec![0, 1]]));
+		assert_eq!(runner.segments()[0].last().unwrap().to, 11);
+	}
+
+	// vehicle 1 drops off ride 1 first, too far away to get to ride 2 in time for the bonus, and only takes it
+	// without booking ahead. Booked ahead, ride 2 goes to vehicle 0, which drops off ride 0 right where it starts
+	let lines = vec![
+		"6 13 2 3 10 50".to_string(),
+		"0 0 0 10 2 50".to_string(),
+		"0 0 5 0 2 50".to_string(),
+		"0 10 0 12 13 50".to_string(),
+	];
+	let problem = Problem::from_lines(&lines).unwrap();
+
+	let mut pickups = Vec::new();
+	for &book_ahead in [false, true].iter() {
+		let mut runner = JobScheduler::from_problem(&problem);
+		runner.set_book_ahead(book_ahead);
+		runner.clear_observers();
+		runner.run().unwrap();
+
+		let (routes, score) = if book_ahead {
+			(vec![vec![0, 2], vec![1]], 47)
+		} else {
+			(vec![vec![0], vec![1, 2]], 37)
+		};
+		assert_eq!(runner.solution(), Solution::new(routes));
+		assert_eq!(runner.solution().score(&problem), Ok(score));
+		let pickup = runner
+			.segments()
+			.iter()
+			.flat_map(|row| row.iter())
+			.find(|seg| seg.job == Some(2) && seg.kind == SegmentKind::OnRide)
+			.map(|seg| seg.from);
+		pickups.push(pickup);
+	}
+	// booked ahead, ride 2 is under way on time for its bonus instead of once vehicle 1 gets there
+	assert_eq!(pickups, vec![Some(22), Some(14)]);
+
+	for strategy in &[
+		DispatchStrategy::Funky,
+		DispatchStrategy::BonusAware {
+			bonus_weight: 1.0,
+			horizon: 20000,
+		},
+	] {
+		let mut runner = load("data/b_should_be_easy.in");
+		runner.set_dispatch_strategy(*strategy);
+		runner.set_book_ahead(true);
+		runner.clear_observers();
+		runner.run().unwrap();
+
+		let problem = load_problem("data/b_should_be_easy.in");
+		assert!(runner.solution().score(&problem).unwrap() > 0);
+		for row in runner.segments() {
+			assert!(row.windows(2).all(|w| w[0].to <= w[1].from));
+		}
+	}
+}
+
+#[test]
+fn solution_scoring() {
+	let problem = load_problem("data/a_example.in");
+
+	// example solution from the problem statement
+	let optimal = Solution::new(vec![vec![0], vec![2, 1]]);
+	assert_eq!(optimal.score(&problem), Ok(10));
+	assert_eq!(optimal.output_as_str(), "1 0\n2 2 1\n");
+
+	// ride 2 can't be finished on time after ride 0
+	let late = Solution::new(vec![vec![0, 2], vec![1]]);
+	assert_eq!(late.score(&problem), Ok(8));
+}
+
+#[test]
+fn solution_validation() {
+	let problem = load_problem("data/a_example.in");
+
+	assert_eq!(
+		Solution::new(vec![vec![0]]).score(&problem),
+		Err(SolutionError::VehicleCountMismatch(2, 1))
+	);
+	assert_eq!(
+		Solution::new(vec![vec![0], vec![3]]).score(&problem),
+		Err(SolutionError::UnknownRide(3))
+	);
+	assert_eq!(
+		Solution::new(vec![vec![0, 1], vec![1]]).score(&problem),
+		Err(SolutionError::DuplicateRide(1))
+	);
+}
+
+#[test]
+fn portfolio_keeps_best() {
+	let problem = load_problem("data/a_example.in");
+	let entries = PortfolioEntry::defaults();
+	let report = run_portfolio(&problem, &entries, 42);
+
+	assert_eq!(report.results().len(), entries.len());
+	for r in report.results() {
+		match r.outcome {
+			EntryOutcome::Scored(score) => assert!(score <= report.best_score()),
+			_ => panic!("{} didn't produce a valid solution", r.name),
+		}
+	}
+
+	let (_, best) = report.best().unwrap();
+	assert_eq!(best.score(&problem), Ok(report.best_score()));
+	assert_eq!(report.best_score(), 10);
+}
+
+#[test]
+fn batch_input_files() {
+	let items = input_files(Path::new("data")).unwrap();
+	let names: Vec<_> = items
+		.iter()
+		.map(|i| i.input.file_name().unwrap().to_str().unwrap().to_string())
+		.collect();
+
+	assert_eq!(
+		names,
+		vec![
+			"a_example.in",
+			"b_should_be_easy.in",
+			"c_no_hurry.in",
+			"d_metropolis.in",
+			"e_high_bonus.in",
+		]
+	);
+	assert_eq!(items[0].output, Path::new("data").join("a_example.o"));
+}
+
+#[test]
+fn batch_keeps_order() {
+	let items = vec![
+		BatchItem::new(PathBuf::from("data/b_should_be_easy.in")),
+		BatchItem::new(PathBuf::from("data/does_not_exist.in")),
+		BatchItem::new(PathBuf::from("data/a_example.in")),
+	];
+
+	let results = run_batch(items, 2, |item, log| {
+		let problem = Problem::new(FileReader::new(item.input.to_str().unwrap())?)?;
+		let mut runner = JobScheduler::from_problem(&problem);
+		runner.clear_observers();
+		runner.run()?;
+
+		*log += &runner.summary();
+		Ok(runner.solution().score(&problem)?)
+	});
+
+	assert_eq!(results.len(), 3);
+	assert_eq!(results[0].item.input, PathBuf::from("data/b_should_be_easy.in"));
+	assert!(results[0].score.unwrap() > 0);
+	assert_eq!(results[1].score, None);
+	assert_eq!(results[2].score, Some(8));
+}
+
+#[test]
+fn errors_instead_of_panics() {
+	let lines = vec!["3 4 2 3 2 10".to_string(), "0 0 1 x 2 9".to_string()];
+	match Problem::from_lines(&lines) {
+		Err(Error::MalformedInput(2, ref line)) if line == "0 0 1 x 2 9" => {}
+		r => panic!("expected MalformedInput, got {:?}", r.map(|p| p.num_jobs())),
+	}
+	match JobScheduler::new(FileReader::from_string("3 4 2 3 2 10\n0 0 1 0 0\n")) {
+		Err(Error::MalformedInput(2, _)) => {}
+		_ => panic!("expected MalformedInput"),
+	}
+
+	// headers that make no sense, or promise a different number of rides
+	let ride = "0 0 1 1 0 9".to_string();
+	for header in &["3 4 2 -1 2 10", "0 4 2 1 2 10", "3 4 -2 1 2 10", "3 4 2 1 2 -10", "3 4 2 2 2 10", "3 4 2 0 2 10"] {
+		match Problem::from_lines(&[header.to_string(), ride.clone()]) {
+			Err(Error::MalformedInput(1, ref line)) if line == header => {}
+			r => panic!("expected MalformedInput for {}, got {:?}", header, r.map(|p| p.num_jobs())),
+		}
+	}
+	match Problem::from_lines(&[]) {
+		Err(Error::MalformedInput(1, _)) => {}
+		_ => panic!("expected MalformedInput for a missing header"),
+	}
+
+	let jobs = vec![Job::new(1, Coord::new(0, 0), Coord::new(1, 1), 0, 9)];
+	match Problem::from_jobs(3, 4, 2, 2, 10, jobs) {
+		Err(Error::MisnumberedJob(0, 1)) => {}
+		_ => panic!("expected MisnumberedJob"),
+	}
+
+	let empty = Problem::from_lines(&["3 4 2 0 2 10".to_string()]).unwrap();
+	let mut runner = JobScheduler::from_problem(&empty);
+	runner.clear_observers();
+	match runner.run() {
+		Err(Error::NoJobs) => {}
+		r => panic!("expected NoJobs, got {:?}", r),
+	}
+
+	// usable as any other error, with a readable message
+	let err: Box<dyn StdError> = Box::new(Error::from(SolutionError::DuplicateRide(3)));
+	assert_eq!(err.to_string(), "Invalid solution: ride 3 is served more than once");
+	assert_eq!(err.source().unwrap().to_string(), "ride 3 is served more than once");
+
+	// down to the OS error, one layer at a time
+	let err = Error::from(FileReader::new("data/does_not_exist.in").err().unwrap());
+	assert!(err.to_string().starts_with("I/O error: couldn't open file: "));
+	let io_err = err.source().and_then(|e| e.source()).unwrap();
+	assert_eq!(io_err.downcast_ref::<io::Error>().unwrap().kind(), io::ErrorKind::NotFound);
+	assert_eq!(
+		Error::JobAlreadyQueued(1, 4).to_string(),
+		"Vehicle 1 was handed job 4 with another one already lined up"
+	);
+}
+
+#[test]
+fn rng_is_deterministic() {
+	let mut a = Rng::new(1234);
+	let mut b = Rng::new(1234);
+	let mut c = Rng::new(1235);
+
+	let seq_a: Vec<u64> = (0..16).map(|_| a.next_u64()).collect();
+	let seq_b: Vec<u64> = (0..16).map(|_| b.next_u64()).collect();
+	let seq_c: Vec<u64> = (0..16).map(|_| c.next_u64()).collect();
+	assert_eq!(seq_a, seq_b);
+	assert_ne!(seq_a, seq_c);
+	assert_eq!(a.seed(), 1234);
+
+	let mut items: Vec<i32> = (0..50).collect();
+	a.shuffle(&mut items);
+	let mut sorted = items.clone();
+	sorted.sort();
+	assert_eq!(sorted, (0..50).collect::<Vec<i32>>());
+
+	for _ in 0..1000 {
+		assert!(a.below(7) < 7);
+		let r = a.range(-3, 3);
+		assert!((-3..3).contains(&r));
+		let f = a.next_f64();
+		assert!((0.0..1.0).contains(&f));
+	}
+}
+
+#[test]
+fn seeded_runs_are_reproducible() {
+	let problem = load_problem("data/b_should_be_easy.in");
+	let run = |seed| {
+		let mut runner = JobScheduler::from_problem(&problem);
+		runner.set_rng(Rng::new(seed));
+		runner.clear_observers();
+		runner.run().unwrap();
+		assert_eq!(runner.seed(), Some(seed));
+		assert!(runner.summary().ends_with(&format!("Seed: {}", seed)));
+		runner.output_as_str()
+	};
+
+	assert_eq!(run(7), run(7));
+
+	let entries = vec![
+		PortfolioEntry::new("funky", DispatchStrategy::Funky, RepositionPolicy::Stay).with_random_ties(),
+	];
+	let first = run_portfolio(&problem, &entries, 99);
+	let second = run_portfolio(&problem, &entries, 99);
+	assert_eq!(first.seed(), 99);
+	assert_eq!(first.results()[0].seed, second.results()[0].seed);
+	assert_eq!(first.best().unwrap().1, second.best().unwrap().1);
+}
+
+/// Runs the binary on a copy of the input and returns the seed line it printed and the solution it wrote
+fn solve_with_binary(dir: &Path, args: &[&str]) -> (String, String) {
+	let input = dir.join("b_should_be_easy.in");
+	let output = Command::new(env!("CARGO_BIN_EXE_google_hashcode18_prelim"))
+		.args(args)
+		.arg(&input)
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let seed_line = stdout.lines().last().unwrap().split(" | ").skip(1).collect::<Vec<_>>().join(" | ");
+	(seed_line, ::std::fs::read_to_string(input.with_extension("o")).unwrap())
+}
+
+#[test]
+fn printed_seed_reproduces_run() {
+	let dir = ::std::env::temp_dir().join("hashcode_printed_seed");
+	::std::fs::create_dir_all(&dir).unwrap();
+	::std::fs::copy("data/b_should_be_easy.in", dir.join("b_should_be_easy.in")).unwrap();
+
+	// passing the seed back must not turn random ties on
+	let (seed_line, first) = solve_with_binary(&dir, &[]);
+	assert!(seed_line.starts_with("Seed: ") && !seed_line.contains("Random ties"));
+	let seed = seed_line.trim_start_matches("Seed: ").to_string();
+	let (_, again) = solve_with_binary(&dir, &["--seed", &seed]);
+	assert_eq!(first, again);
+
+	let (seed_line, first) = solve_with_binary(&dir, &["--random-ties"]);
+	assert!(seed_line.ends_with(" | Random ties"));
+	let seed = seed_line.trim_start_matches("Seed: ").trim_end_matches(" | Random ties").to_string();
+	let (_, again) = solve_with_binary(&dir, &["--seed", &seed, "--random-ties"]);
+	assert_eq!(first, again);
+
+	::std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn routes_track_official_score() {
+	let problem = load_problem("data/b_should_be_easy.in");
+	let mut rng = Rng::new(5);
+	let greedy = solver::greedy(
+		&problem,
+		DispatchStrategy::Funky,
+		RepositionPolicy::Stay,
+		&mut rng,
+	).unwrap();
+
+	let mut routes = Routes::new(&problem, &greedy).unwrap();
+	assert_eq!(routes.score(), greedy.score(&problem).unwrap());
+
+	let mut removed = routes.assigned();
+	rng.shuffle(&mut removed);
+	removed.truncate(30);
+	for &ride in removed.iter() {
+		assert!(routes.remove(ride));
+		assert_eq!(routes.vehicle_of(ride), None);
+		assert_eq!(routes.score(), routes.to_solution().score(&problem).unwrap());
+	}
+
+	for ride in routes.unassigned() {
+		if let Some(ins) = routes.best_insertion(ride) {
+			let before = routes.score();
+			routes.insert(&ins);
+			assert_eq!(routes.score(), before + ins.score);
+			assert_eq!(routes.score(), routes.to_solution().score(&problem).unwrap());
+		}
+	}
+}
+
+#[test]
+fn simulation_scores_every_pickup() {
+	// vehicle 0 is at the start of ride 0 when it's loaded in step 2, its earliest start, and vehicle 1 drops off
+	// ride 2 right where ride 1 starts, so both rides are picked up in the step they're loaded
+	let problem = load_problem("data/a_example.in");
+	let solution = Solution::new(vec![vec![0], vec![2, 1]]);
+
+	let mut runner = JobScheduler::from_problem(&problem);
+	runner.follow_solution(&solution);
+	runner.clear_observers();
+	runner.run().unwrap();
+	assert_eq!(runner.calculate_score(), solution.score(&problem).unwrap());
+	assert_eq!(runner.calculate_score(), 10);
+}
+
+#[test]
+fn lns_improves_on_greedy() {
+	let problem = load_problem("data/b_should_be_easy.in");
+	let params = LnsParams {
+		iterations: 100,
+		..LnsParams::default()
+	};
+
+	let greedy = solver::solve(
+		&problem,
+		&Solver::Greedy(params.initial, RepositionPolicy::Stay),
+		&mut Rng::new(3),
+	).unwrap();
+	let lns = solver::solve(&problem, &Solver::Lns(params.clone()), &mut Rng::new(3)).unwrap();
+
+	assert!(lns.score(&problem).unwrap() >= greedy.score(&problem).unwrap());
+	assert_eq!(lns, solver::solve(&problem, &Solver::Lns(params), &mut Rng::new(3)).unwrap());
+}
+
+#[test]
+fn insertion_recovers_rides() {
+	let problem = load_problem("data/a_example.in");
+	let (solution, report) = solver::insert_unassigned(&problem, &Solution::new(vec![vec![0], vec![]])).unwrap();
+
+	assert_eq!(report.score_before, 6);
+	assert_eq!(report.recovered, 2);
+	assert_eq!(report.points, report.score_after - report.score_before);
+	assert_eq!(solution.score(&problem), Ok(report.score_after));
+
+	// solutions that don't fit the problem are turned away instead of written out
+	match solver::insert_unassigned(&problem, &Solution::new(vec![vec![0], vec![3]])) {
+		Err(SolutionError::UnknownRide(3)) => {}
+		r => panic!("expected UnknownRide, got {:?}", r.map(|(s, _)| s)),
+	}
+	match solver::insert_unassigned(&problem, &Solution::new(vec![vec![0], vec![2, 0]])) {
+		Err(SolutionError::DuplicateRide(0)) => {}
+		r => panic!("expected DuplicateRide, got {:?}", r.map(|(s, _)| s)),
+	}
+	assert_eq!(
+		Routes::new(&problem, &Solution::new(vec![vec![0], vec![3]])).err(),
+		Some(SolutionError::UnknownRide(3))
+	);
+
+	let problem = load_problem("data/b_should_be_easy.in");
+	let greedy = solver::solve(
+		&problem,
+		&Solver::Greedy(DispatchStrategy::Funky, RepositionPolicy::Stay),
+		&mut Rng::new(5),
+	).unwrap();
+	let (solution, report) = solver::insert_unassigned(&problem, &greedy).unwrap();
+	assert_eq!(solution.score(&problem), Ok(report.score_after));
+	assert!(report.score_after >= report.score_before);
+
+	// rides that were already earning points keep every one of them
+	let before = Routes::new(&problem, &greedy).unwrap();
+	let after = Routes::new(&problem, &solution).unwrap();
+	let score_of = |routes: &Routes, id| {
+		let v = routes.vehicle_of(id).unwrap();
+		routes.timings(v).iter().find(|t| t.id == id).unwrap().score
+	};
+	for id in before.assigned() {
+		assert!(score_of(&after, id) >= score_of(&before, id));
+	}
+}
+
+#[test]
+fn genetic_improves_on_greedy() {
+	let problem = load_problem("data/b_should_be_easy.in");
+	let params = GaParams {
+		population: 8,
+		generations: 10,
+		..GaParams::default()
+	};
+
+	let greedy = solver::solve(
+		&problem,
+		&Solver::Greedy(params.initial, RepositionPolicy::Stay),
+		&mut Rng::new(9),
+	).unwrap();
+	let evolved = solver::solve(&problem, &Solver::Genetic(params.clone()), &mut Rng::new(9)).unwrap();
+
+	assert!(evolved.score(&problem).unwrap() >= greedy.score(&problem).unwrap());
+	assert_eq!(evolved, solver::solve(&problem, &Solver::Genetic(params), &mut Rng::new(9)).unwrap());
+}
+
+#[test]
+fn tabu_improves_on_greedy() {
+	let problem = load_problem("data/b_should_be_easy.in");
+	let params = TabuParams {
+		iterations: 300,
+		..TabuParams::default()
+	};
+
+	let greedy = solver::solve(
+		&problem,
+		&Solver::Greedy(params.initial, RepositionPolicy::Stay),
+		&mut Rng::new(4),
+	).unwrap();
+	let improved = solver::solve(&problem, &Solver::Tabu(params.clone()), &mut Rng::new(4)).unwrap();
+
+	assert!(improved.score(&problem).unwrap() >= greedy.score(&problem).unwrap());
+	assert_eq!(improved, solver::solve(&problem, &Solver::Tabu(params), &mut Rng::new(4)).unwrap());
+}
+
+/// Small random instance, in the input file format
+fn random_problem(rng: &mut Rng, vehicles: usize, rides: usize) -> Problem {
+	let max_tsteps = 40;
+	let mut lines = vec![format!("10 10 {} {} 2 {}", vehicles, rides, max_tsteps)];
+	for _ in 0..rides {
+		let (a, b, x, y) = (rng.range(0, 10), rng.range(0, 10), rng.range(0, 10), rng.range(0, 10));
+		let earliest_start = rng.range(0, max_tsteps / 2);
+		let latest_finish = earliest_start + (a - x).abs() + (b - y).abs() + rng.range(0, 15);
+		lines.push(format!("{} {} {} {} {} {}", a, b, x, y, earliest_start, latest_finish));
+	}
+
+	Problem::from_lines(&lines).unwrap()
+}
+
+#[test]
+fn exact_solver_bounds_heuristics() {
+	let params = ExactParams::default();
+	let problem = load_problem("data/a_example.in");
+	assert_eq!(solver::exact(&problem, &params).unwrap().score(&problem), Ok(10));
+
+	let problem = load_problem("data/b_should_be_easy.in");
+	assert_eq!(
+		solver::exact(&problem, &params),
+		Err(ExactError::TooLarge(problem.num_jobs() as usize, params.max_rides))
+	);
+
+	let mut rng = Rng::new(11);
+	for _ in 0..20 {
+		let problem = random_problem(&mut rng, 2, 7);
+		let optimum = solver::exact(&problem, &params).unwrap().score(&problem).unwrap();
+
+		for strategy in [DispatchStrategy::Funky, LnsParams::default().initial] {
+			let greedy = solver::solve(&problem, &Solver::Greedy(strategy, RepositionPolicy::Stay), &mut rng).unwrap();
+			let (inserted, _) = solver::insert_unassigned(&problem, &greedy).unwrap();
+			assert!(greedy.score(&problem).unwrap() <= inserted.score(&problem).unwrap());
+			assert!(inserted.score(&problem).unwrap() <= optimum);
+		}
+
+		let lns = solver::solve(&problem, &Solver::Lns(LnsParams::default()), &mut rng).unwrap();
+		assert!(lns.score(&problem).unwrap() <= optimum);
+	}
+
+	let params = ExactParams {
+		max_rides: 40,
+		time_limit: Some(Duration::from_millis(0)),
+	};
+	let problem = random_problem(&mut rng, 5, 40);
+	match solver::exact(&problem, &params) {
+		Err(ExactError::TimedOut(best)) => assert!(best.validate(&problem).is_ok()),
+		other => panic!("expected a timeout, got {:?}", other),
+	}
+}
+
+#[test]
+fn single_vehicle_sequencing() {
+	let mut rng = Rng::new(21);
+	for _ in 0..10 {
+		let problem = random_problem(&mut rng, 1, 8);
+		let mut rides: Vec<i32> = (0..8).collect();
+		rng.shuffle(&mut rides);
+
+		// one vehicle, so the exact solver gives the best order as well
+		let order = solver::sequence(&problem, &rides);
+		let optimum = solver::exact(&problem, &ExactParams::default()).unwrap();
+		assert_eq!(route_score(&problem, &order), optimum.score(&problem).unwrap());
+		assert!(route_timings(&problem, &order).iter().all(|t| t.on_time()));
+	}
+
+	// too many rides for the exact search
+	let problem = load_problem("data/b_should_be_easy.in");
+	let greedy = solver::solve(
+		&problem,
+		&Solver::Greedy(DispatchStrategy::Funky, RepositionPolicy::Stay),
+		&mut rng,
+	).unwrap();
+	for rides in greedy.rides().iter().filter(|r| r.len() > solver::sequence::EXACT_RIDES) {
+		let order = solver::sequence(&problem, rides);
+		assert!(route_score(&problem, &order) >= route_score(&problem, rides));
+
+		let mut sorted = order.clone();
+		sorted.sort();
+		sorted.dedup();
+		assert_eq!(sorted.len(), order.len());
+		assert!(order.iter().all(|id| rides.contains(id)));
+	}
+}
+
+#[test]
+fn lp_round_trip() {
+	let problem = load_problem("data/a_example.in");
+	let slice = Slice::all(&problem);
+	let model = lp::model_as_str(&problem, &slice);
+	for section in ["Maximize", "Subject To", "Bounds", "Binary", "End"] {
+		assert!(model.lines().any(|l| l == section));
+	}
+	for var in ["x_o_0", "x_o_2", "x_2_1", "y_1", "b_1", "s_1"] {
+		assert!(model.contains(var));
+	}
+
+	let empty = Solution::new(vec![Vec::new(); 2]);
+	let lines = |l: &[&str]| l.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+	// `name value` columns, and CPLEX's XML
+	let plain = lines(&["# Objective value = 10", "x_o_0 1", "x_o_2 1", "x_2_1 1", "x_0_1 0", "y_0 1"]);
+	let solution = lp::parse_assignment(&problem, &slice, &empty, &plain).unwrap();
+	assert_eq!(solution.score(&problem), Ok(10));
+
+	let xml = lines(&[
+		"<variable name=\"x_o_0\" index=\"0\" value=\"1\"/>",
+		"<variable name=\"x_o_2\" index=\"1\" value=\"0.9999999\"/>",
+		"<variable name=\"x_2_1\" index=\"2\" value=\"1\"/>",
+	]);
+	assert_eq!(lp::parse_assignment(&problem, &slice, &empty, &xml).unwrap(), solution);
+
+	let cycle = lines(&["x_o_0 1", "x_1_2 1", "x_2_1 1"]);
+	match lp::parse_assignment(&problem, &slice, &empty, &cycle) {
+		Err(LpError::Unreachable(_)) => {}
+		other => panic!("expected a cycle to be rejected, got {:?}", other),
+	}
+
+	// a slice keeps the other vehicles' rides, and late rides are caught by the scorer
+	let slice = Slice::new(vec![1, 2], vec![1]);
+	let base = Solution::new(vec![vec![0], vec![]]);
+	let solution = lp::parse_assignment(&problem, &slice, &base, &lines(&["x_o_2 1", "x_2_1 1"])).unwrap();
+	assert_eq!(solution.rides(), &[vec![0], vec![2, 1]][..]);
+	let late = lines(&["x_o_0 1", "x_0_1 1", "x_1_2 1"]);
+	match lp::parse_assignment(&problem, &Slice::all(&problem), &empty, &late) {
+		Err(LpError::Late(2)) => {}
+		other => panic!("expected a late ride, got {:?}", other),
+	}
+}
+
+#[test]
+fn benchmark_instances() {
+	let lines = |s: &str| s.lines().map(|l| l.to_string()).collect::<Vec<_>>();
+
+	let solomon = lines(
+		"C101
+
+VEHICLE
+NUMBER     CAPACITY
+  2         200
+
+CUSTOMER
+CUST NO.  XCOORD.   YCOORD.    DEMAND   READY TIME  DUE DATE   SERVICE   TIME
+
+    0      40         50          0          0       1236          0
+    1      45         68         10        912        967         90
+    2      45         70         30        825        870         90
+    3      42         66         10         65        146         90",
+	);
+	let problem = benchmark::solomon_from_lines(&solomon).unwrap();
+	assert_eq!(problem.num_vehicles(), 2);
+	assert_eq!(problem.num_jobs(), 3);
+	assert_eq!(problem.max_tsteps(), 1236);
+	let ride = problem.job(0);
+	assert!(ride.start().is_origin());
+	assert_eq!((ride.end().x, ride.end().y), (5, 18));
+	assert_eq!((ride.earliest_start(), ride.latest_finish()), (912 - 23, 967));
+
+	let li_lim = lines(
+		"2	200	1
+0	40	50	0	0	1236	0	0	0
+1	45	68	-10	912	967	90	3	0
+2	45	70	-20	825	870	90	4	0
+3	42	66	10	65	146	90	0	1
+4	42	68	20	727	782	90	0	2",
+	);
+	let problem = benchmark::li_lim_from_lines(&li_lim).unwrap();
+	assert_eq!(problem.num_jobs(), 2);
+	let ride = problem.job(0);
+	assert_eq!((ride.start().x, ride.start().y), (2, 16));
+	assert_eq!((ride.end().x, ride.end().y), (5, 18));
+	assert_eq!((ride.earliest_start(), ride.latest_finish()), (912 - 5, 967));
+
+	// the imported instances run through the scheduler like any other
+	let mut runner = JobScheduler::from_problem(&problem);
+	runner.clear_observers();
+	runner.run().unwrap();
+	assert!(runner.solution().validate(&problem).is_ok());
+	let (inserted, _) = solver::insert_unassigned(&problem, &runner.solution()).unwrap();
+	assert_eq!(inserted.score(&problem), Ok(10));
+
+	let broken = lines("2	200	1\n0	40	50	0	0	1236	0	0	0\n1	45	68	-10	912	967	90	3	0");
+	match benchmark::li_lim_from_lines(&broken) {
+		Err(BenchmarkError::UnpairedTask(1)) => {}
+		other => panic!("expected an unpaired pickup, got {:?}", other.map(|p| p.num_jobs())),
+	}
+}
+
+#[test]
+fn compatibility_graph() {
+	let problem = load_problem("data/a_example.in");
+	let solution = Solution::new(vec![vec![0], vec![2, 1]]);
+
+	let graph = dot::compatibility_dot(&problem, &GraphFilter::default(), Some(&solution));
+	assert!(graph.starts_with("digraph"));
+	assert!(graph.contains("\t2 -> 1 [label=\"d=1 w=0\", color=red, penwidth=2];"));
+	assert!(graph.contains("\t0 -> 1 [label=\"d=1 w=0\"];"));
+	assert_eq!(graph.matches("fillcolor=lightblue").count(), 3);
+
+	// ride 0 can't start before step 2
+	let filter = GraphFilter {
+		window: Some((0, 1)),
+		..GraphFilter::default()
+	};
+	let graph = dot::compatibility_dot(&problem, &filter, None);
+	assert!(!graph.contains("\t0 ["));
+	assert!(graph.contains("\t1 -> 2 "));
+
+	let filter = GraphFilter {
+		region: Some((Coord::new(0, 0), Coord::new(1, 3))),
+		..GraphFilter::default()
+	};
+	let graph = dot::compatibility_dot(&problem, &filter, None);
+	assert!(graph.contains("\t0 [") && graph.contains("\t1 [") && !graph.contains("\t2 ["));
+}
+
+#[test]
+fn gantt_chart() {
+	let problem = load_problem("data/a_example.in");
+	let solution = Solution::new(vec![vec![0], vec![2, 1]]);
+
+	let mut replay = JobScheduler::from_problem(&problem);
+	replay.follow_solution(&solution);
+	replay.clear_observers();
+	replay.run().unwrap();
+	assert_eq!(replay.solution(), solution);
+
+	let segments = replay.segments();
+	assert_eq!(segments.len(), 2);
+	for (route, row) in solution.rides().iter().zip(segments.iter()) {
+		let on_ride: Vec<i32> = row
+			.iter()
+			.filter(|s| s.kind == SegmentKind::OnRide)
+			.map(|s| s.job.unwrap())
+			.collect();
+		assert_eq!(&on_ride, route);
+
+		// tasks follow each other without overlapping
+		assert!(row.windows(2).all(|w| w[0].to <= w[1].from));
+	}
+	// jobs are handed out in the first step and the vehicles move off in the next
+	assert_eq!(
+		segments[0],
+		vec![Segment {
+			kind: SegmentKind::OnRide,
+			job: Some(0),
+			from: 2,
+			to: 6,
+		}]
+	);
+
+	let svg = gantt::gantt_svg(&problem, &segments);
+	assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+	assert_eq!(svg.matches("<circle").count(), 3);
+}
+
+#[test]
+fn heatmaps() {
+	let problem = load_problem("data/a_example.in");
+
+	// 3x4 grid in blocks of 2x2
+	let starts = Heatmap::density(&problem, Density::Starts, None, 2);
+	assert_eq!((starts.height(), starts.width()), (2, 2));
+	assert_eq!(
+		(starts.count(0, 0), starts.count(0, 1), starts.count(1, 0), starts.count(1, 1)),
+		(1, 1, 1, 0)
+	);
+	let ends = Heatmap::density(&problem, Density::Ends, None, 2);
+	assert_eq!(
+		(ends.count(0, 0), ends.count(0, 1), ends.count(1, 0), ends.count(1, 1)),
+		(1, 1, 0, 1)
+	);
+
+	// without a solution every ride is missed, with one only the rest
+	let missed = Heatmap::density(&problem, Density::Missed, None, 1);
+	assert_eq!((missed.height(), missed.width()), (3, 4));
+	assert_eq!(missed.max(), 1);
+	let solution = Solution::new(vec![vec![0], vec![]]);
+	let missed = Heatmap::density(&problem, Density::Missed, Some(&solution), 2);
+	assert_eq!(missed.count(0, 0), 0);
+	assert_eq!(missed.count(0, 1) + missed.count(1, 0), 2);
+
+	assert_eq!(starts.to_pgm(), "P2\n2 2\n255\n255 255\n255 0\n");
+	let ppm = missed.to_ppm();
+	assert!(ppm.starts_with("P3\n2 2\n255\n"));
+	assert_eq!(ppm.lines().nth(3), Some("0 0 0 255 255 255"));
+}
+
+#[test]
+fn animation_frames() {
+	let problem = load_problem("data/a_example.in");
+	let solution = Solution::new(vec![vec![0], vec![2, 1]]);
+
+	// at step 1 vehicle 0 waits for ride 0 and vehicle 1 is on its way to ride 2
+	let svg = frames::frame_svg(&problem, &solution, 1);
+	assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+	assert!(svg.contains("step 1 / 10"));
+	assert_eq!(svg.matches("<circle").count(), 2);
+	assert_eq!(svg.matches("<line").count(), 1);
+	assert_eq!(svg.matches("fill=\"#bbbbbb\"").count(), 3);
+
+	// by step 3 both are under way and only ride 1 is left waiting
+	let svg = frames::frame_svg(&problem, &solution, 3);
+	assert_eq!(svg.matches("<line").count(), 2);
+	assert_eq!(svg.matches("fill=\"#bbbbbb\"").count(), 1);
+
+	// everything is over by the end, both vehicles sit still
+	let svg = frames::frame_svg(&problem, &solution, 10);
+	assert_eq!(svg.matches("<line").count(), 0);
+	assert_eq!(svg.matches("fill=\"#bbbbbb\"").count(), 0);
+
+	let dir = std::env::temp_dir().join("hashcode_frames");
+	let count = frames::write_frames(&problem, &solution, 3, dir.to_str().unwrap()).unwrap();
+	assert_eq!(count, 4);
+	assert!(dir.join("frame_00003.svg").exists());
+	std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn interpolated_positions() {
+	// along x first, then y, stopping at the target
+	let from = Coord::new(0, 0);
+	assert_eq!(from.towards(&Coord::new(2, 3), 3), Coord::new(2, 1));
+	assert_eq!(from.towards(&Coord::new(2, 3), 10), Coord::new(2, 3));
+	assert_eq!(from.towards(&Coord::new(-2, -1), 3), Coord::new(-2, -1));
+	assert_eq!(from.towards(&Coord::new(2, 3), -1), from);
+
+	let problem = load_problem("data/a_example.in");
+	let solution = Solution::new(vec![vec![0], vec![2, 1]]);
+	let at = |step| solution.positions(&problem, step).unwrap();
+	assert_eq!(at(0), vec![Coord::new(0, 0), Coord::new(0, 0)]);
+	// vehicle 0 waits for ride 0 to become available, vehicle 1 heads for ride 2
+	assert_eq!(at(1), vec![Coord::new(0, 0), Coord::new(1, 0)]);
+	assert_eq!(at(3), vec![Coord::new(1, 0), Coord::new(2, 1)]);
+	assert_eq!(at(6), vec![Coord::new(1, 3), Coord::new(1, 1)]);
+	// and both stay where they drop off their last ride
+	assert_eq!(at(100), vec![Coord::new(1, 3), Coord::new(1, 0)]);
+	assert_eq!(
+		Solution::new(vec![vec![0], vec![7]]).positions(&problem, 3),
+		Err(SolutionError::UnknownRide(7))
+	);
+
+	// a live simulation moves every vehicle by at most one intersection per step
+	let mut scheduler = JobScheduler::from_problem(&problem);
+	scheduler.follow_solution(&solution);
+	scheduler.clear_observers();
+	let mut last = scheduler.positions();
+	while scheduler.step().unwrap() {
+		let now = scheduler.positions();
+		assert!(last.iter().zip(now.iter()).all(|(a, b)| a.dist(b) <= 1));
+		if scheduler.current_step() == 2 {
+			assert_eq!(now, vec![Coord::new(1, 0), Coord::new(1, 0)]);
+		}
+		if scheduler.current_step() == 3 {
+			// halfway through ride 0, which vehicle 0 started right at its earliest start like the scorer has it
+			assert_eq!(now[0], Coord::new(1, 1));
+			assert_eq!(now[0], at(4)[0]);
+			// vehicle 1 set off two steps late and hasn't waited for a ride since to make that up
+			assert_eq!(now[1], Coord::new(2, 0));
+			assert_eq!(now[1], at(2)[1]);
+		}
+		last = now;
+	}
+	assert_eq!(scheduler.current_step(), problem.max_tsteps() - 1);
+	assert_eq!(last, at(100));
+}
+
+#[test]
+fn dashboard_rendering() {
+	let problem = load_problem("data/a_example.in");
+	let dashboard = Dashboard::new(Duration::from_secs(1)).with_map(&problem, 8);
+	assert!(dashboard.is_due() && dashboard.wants_positions());
+
+	let status = Status {
+		step: 5,
+		max_tsteps: 10,
+		busy: 1,
+		idle: 1,
+		remaining_jobs: 1,
+		score: 4,
+		positions: vec![Coord::new(0, 0), Coord::new(2, 3)],
+	};
+	let lines: Vec<String> = dashboard.render(&status).lines().map(|l| l.to_string()).collect();
+	assert!(lines[0].starts_with("Step: 5 / 10 (50.0%)"));
+	assert_eq!(lines[1], "Vehicles: 1 busy, 1 idle");
+	assert_eq!(lines[2], "Remaining jobs: 1 | Score: 4");
+	// 3x4 grid on 8 characters across and 3 down
+	assert_eq!(lines[3], "+--------+");
+	assert_eq!(lines[4], "|@       |");
+	assert_eq!(lines[6], "|      @ |");
+	assert_eq!(lines.len(), 8);
+
+	// the simulation draws as it goes without changing its outcome
+	let mut plain = load("data/b_should_be_easy.in");
+	plain.clear_observers();
+	plain.run().unwrap();
+	let mut watched = load("data/b_should_be_easy.in");
+	watched.clear_observers();
+	watched.set_dashboard(Dashboard::new(Duration::from_secs(60)));
+	watched.run().unwrap();
+	assert_eq!(plain.solution(), watched.solution());
+}
+
+#[test]
+fn dashboard_stays_off_stdout() {
+	let input = ::std::fs::read("data/a_example.in").unwrap();
+	let mut child = Command::new(env!("CARGO_BIN_EXE_google_hashcode18_prelim"))
+		.args(["--dashboard", "-"])
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.unwrap();
+	child.stdin.take().unwrap().write_all(&input).unwrap();
+	let output = child.wait_with_output().unwrap();
+	assert!(output.status.success());
+
+	// the solution on stdout has to stay readable, the dashboard goes to stderr
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert!(!stdout.contains('\x1b'));
+	assert!(String::from_utf8_lossy(&output.stderr).contains("\x1b[2J"));
+	let problem = load_problem("data/a_example.in");
+	let lines: Vec<String> = stdout.lines().map(|l| l.to_string()).collect();
+	let solution = Solution::from_lines(&lines).unwrap();
+	assert!(solution.score(&problem).unwrap() > 0);
+}
+
+#[test]
+fn stdin_rejects_file_outputs() {
+	let dir = ::std::env::temp_dir().join("hashcode_stdin_outputs");
+	::std::fs::create_dir_all(&dir).unwrap();
+	let input = ::std::fs::read("data/a_example.in").unwrap();
+
+	// these name their files after the input, which would come out as -.dot and the like
+	for args in &[["--gantt"].as_ref(), &["--dot", "0:10"], &["--heatmap", "2"], &["--frames", "5"]] {
+		let mut child = Command::new(env!("CARGO_BIN_EXE_google_hashcode18_prelim"))
+			.args(*args)
+			.arg("-")
+			.current_dir(&dir)
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.spawn()
+			.unwrap();
+		// it may well quit before reading any of it
+		let _ = child.stdin.take().unwrap().write_all(&input);
+		let output = child.wait_with_output().unwrap();
+		assert!(!output.status.success());
+		assert!(output.stdout.is_empty());
+	}
+	assert_eq!(::std::fs::read_dir(&dir).unwrap().count(), 0);
+	::std::fs::remove_dir_all(dir).unwrap();
+}
+
+/// Everything the simulation reported, shared with the test
+#[derive(Default)]
+struct EventLog {
+	ticks: usize,
+	assigned: Vec<(usize, i32)>,
+	started: Vec<(i32, bool)>,
+	completed: Vec<(i32, bool)>,
+	ended: bool,
+}
+
+struct Recorder(Rc<RefCell<EventLog>>);
+
+impl Observer for Recorder {
+	fn on_tick(&mut self, _step: i32) {
+		self.0.borrow_mut().ticks += 1;
+	}
+
+	fn on_assign(&mut self, _step: i32, vehicle: usize, job: i32) {
+		self.0.borrow_mut().assigned.push((vehicle, job));
+	}
+
+	fn on_job_start(&mut self, _step: i32, _vehicle: usize, job: i32, bonus: bool) {
+		self.0.borrow_mut().started.push((job, bonus));
+	}
+
+	fn on_job_complete(&mut self, _step: i32, _vehicle: usize, job: i32, on_time: bool) {
+		self.0.borrow_mut().completed.push((job, on_time));
+	}
+
+	fn on_end(&mut self, scheduler: &JobScheduler) {
+		assert_eq!(scheduler.current_step(), 9);
+		self.0.borrow_mut().ended = true;
+	}
+}
+
+#[test]
+fn simulation_observers() {
+	let problem = load_problem("data/a_example.in");
+	let solution = Solution::new(vec![vec![0], vec![2, 1]]);
+
+	let logs: Vec<Rc<RefCell<EventLog>>> = (0..2).map(|_| Rc::new(RefCell::new(EventLog::default()))).collect();
+	let mut runner = JobScheduler::from_problem(&problem);
+	runner.follow_solution(&solution);
+	runner.clear_observers();
+	for log in logs.iter() {
+		runner.add_observer(Box::new(Recorder(log.clone())));
+	}
+	runner.run().unwrap();
+
+	for log in logs.iter() {
+		let log = log.borrow();
+		assert_eq!(log.ticks, 9);
+		assert_eq!(log.assigned, vec![(0, 0), (1, 2), (1, 1)]);
+		// ride 0 is picked up right at its earliest start, in step 2
+		assert_eq!(log.started, vec![(0, true), (2, false), (1, false)]);
+		assert_eq!(log.completed, vec![(0, true), (2, true), (1, true)]);
+		assert!(log.ended);
+	}
+	// rides picked up as soon as they're loaded count towards the score too
+	assert_eq!(runner.calculate_score(), solution.score(&problem).unwrap());
+}
+